@@ -0,0 +1,78 @@
+//! Timing benchmarks for the hot serialization and deserialization paths.
+//!
+//! Run with `cargo bench`. Each benchmark reports the mean time per iteration, measured over
+//! enough iterations to take roughly half a second.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const TARGET: Duration = Duration::from_millis(500);
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+	// Warm up, and estimate how many iterations fit into the target time.
+	let start = Instant::now();
+	let mut iterations = 0u32;
+	while start.elapsed() < TARGET / 10 {
+		black_box(f());
+		iterations += 1;
+	}
+	let iterations = iterations * 10;
+
+	let start = Instant::now();
+	for _ in 0..iterations {
+		black_box(f());
+	}
+	let per_iteration = start.elapsed() / iterations;
+	println!("{name:<40} {per_iteration:>12.2?}");
+}
+
+fn roundtrip<T>(name: &str, value: &T)
+where
+	T: Serialize + for<'de> Deserialize<'de>,
+{
+	let bytes = storekey::serialize(value).unwrap();
+	bench(&format!("{name}/serialize"), || storekey::serialize(black_box(value)).unwrap());
+	bench(&format!("{name}/deserialize"), || {
+		storekey::deserialize::<T>(black_box(&bytes)).unwrap()
+	});
+	bench(&format!("{name}/deserialize_from"), || {
+		storekey::deserialize_from::<_, T>(black_box(&bytes[..])).unwrap()
+	});
+}
+
+#[derive(Serialize, Deserialize)]
+struct Nested {
+	id: u64,
+	name: String,
+	children: Vec<Nested>,
+}
+
+fn nested(depth: u32) -> Nested {
+	Nested {
+		id: depth as u64,
+		name: format!("node-{depth}"),
+		children: if depth == 0 {
+			vec![]
+		} else {
+			vec![nested(depth - 1), nested(depth - 1)]
+		},
+	}
+}
+
+fn main() {
+	let bytes: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+	roundtrip("bytes/64KiB", &serde_bytes::ByteBuf::from(bytes.clone()));
+	roundtrip("vec_u8/64KiB", &bytes);
+
+	roundtrip("nested/depth-8", &nested(8));
+
+	let map: BTreeMap<String, u64> = (0..10_000).map(|i| (format!("key-{i}"), i)).collect();
+	roundtrip("btree_map/10k", &map);
+
+	roundtrip("u64", &0x0123_4567_89ab_cdefu64);
+	roundtrip("i32", &-42i32);
+	roundtrip("f64", &1.5f64);
+	roundtrip("tuple", &(1u8, -2i16, 3u32, "four".to_owned()));
+}
@@ -0,0 +1,42 @@
+//! Canonical serialization of [`BinaryHeap`] values, for use with `#[serde(with)]`.
+//!
+//! serde's own `BinaryHeap` implementation serializes the elements in the heap's internal order,
+//! which depends on the order they were pushed in, so equal heaps may serialize to different
+//! bytes. This module instead serializes the elements in ascending order, exactly like a sorted
+//! `Vec`, so that equal heaps always serialize identically. The heap is rebuilt when
+//! deserializing, so its internal order is not preserved.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use std::collections::BinaryHeap;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Queue {
+//!     #[serde(with = "storekey::binary_heap")]
+//!     pending: BinaryHeap<u64>,
+//! }
+//! ```
+
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use std::collections::BinaryHeap;
+
+/// Serialize a `BinaryHeap` as a sequence of its elements in ascending order.
+pub fn serialize<T, S>(heap: &BinaryHeap<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+	T: Serialize + Ord,
+	S: Serializer,
+{
+	let mut elements: Vec<&T> = heap.iter().collect();
+	elements.sort();
+	elements.serialize(serializer)
+}
+
+/// Deserialize a `BinaryHeap` from a sequence of its elements.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<BinaryHeap<T>, D::Error>
+where
+	T: Deserialize<'de> + Ord,
+	D: Deserializer<'de>,
+{
+	Vec::deserialize(deserializer).map(BinaryHeap::from)
+}
@@ -0,0 +1,116 @@
+//! Byte buffers which are encoded as bytes rather than as sequences.
+//!
+//! [`ByteString`] and [`ByteSlice`] are encoded as a `u64` length followed by the raw bytes,
+//! whereas a `Vec<u8>` or `&[u8]` is encoded as a sequence of bytes followed by a terminator. The
+//! two encodings are not interchangeable: a `ByteString` can not be deserialized from an encoded
+//! `Vec<u8>` or the other way around, and they sort differently. Encoded byte buffers sort by
+//! length first and only then by content, so `ByteString(vec![2])` sorts before
+//! `ByteString(vec![1, 1])` even though `vec![2]` sorts after `vec![1, 1]`. For that reason
+//! neither type implements `Ord`.
+
+use serde::de::{Deserialize, Deserializer, Error, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+
+/// An owned byte buffer which serializes like a `&[u8]` passed to `serialize_bytes`, and whose
+/// `Debug` output is a byte string literal such as `b"key\x00\xff"`.
+///
+/// See the [module documentation](self) for how it is encoded.
+#[derive(Clone, Default, Hash, PartialEq, Eq)]
+pub struct ByteString(pub Vec<u8>);
+
+/// A borrowed byte slice which serializes like a `&[u8]` passed to `serialize_bytes`, and whose
+/// `Debug` output is a byte string literal such as `b"key\x00\xff"`.
+///
+/// It is encoded exactly like a [`ByteString`]. Deserializing a `ByteSlice` borrows from the
+/// input, so it can only be deserialized from a slice.
+#[derive(Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct ByteSlice<'a>(pub &'a [u8]);
+
+fn fmt_bytes(bytes: &[u8], f: &mut fmt::Formatter) -> fmt::Result {
+	f.write_str("b\"")?;
+	for b in bytes {
+		fmt::Display::fmt(&b.escape_ascii(), f)?;
+	}
+	f.write_str("\"")
+}
+
+impl fmt::Debug for ByteString {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt_bytes(&self.0, f)
+	}
+}
+
+impl<'a> fmt::Debug for ByteSlice<'a> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		fmt_bytes(self.0, f)
+	}
+}
+
+impl Serialize for ByteString {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_bytes(&self.0)
+	}
+}
+
+impl<'a> Serialize for ByteSlice<'a> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_bytes(self.0)
+	}
+}
+
+impl<'de> Deserialize<'de> for ByteString {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		struct ByteStringVisitor;
+
+		impl<'de> Visitor<'de> for ByteStringVisitor {
+			type Value = ByteString;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a byte buffer")
+			}
+
+			fn visit_bytes<E: Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+				Ok(ByteString(v.to_vec()))
+			}
+
+			fn visit_byte_buf<E: Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+				Ok(ByteString(v))
+			}
+		}
+
+		deserializer.deserialize_byte_buf(ByteStringVisitor)
+	}
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for ByteSlice<'a> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		struct ByteSliceVisitor;
+
+		impl<'de> Visitor<'de> for ByteSliceVisitor {
+			type Value = ByteSlice<'de>;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a borrowed byte slice")
+			}
+
+			fn visit_borrowed_bytes<E: Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+				Ok(ByteSlice(v))
+			}
+		}
+
+		deserializer.deserialize_bytes(ByteSliceVisitor)
+	}
+}
@@ -0,0 +1,95 @@
+//! CRC-32 checksums for detecting corrupt serialized values.
+//!
+//! A [`ChecksumWriter`] wraps any writer, including the one passed to a [`Serializer`], and
+//! computes the checksum of everything written through it. The checksum can be appended to the
+//! output, and checked and stripped again with [`verify`] before deserializing.
+//!
+//! ```
+//! use serde::Serialize;
+//! use storekey::checksum::{verify, ChecksumWriter};
+//! use storekey::Serializer;
+//!
+//! let mut writer = ChecksumWriter::new(Vec::new());
+//! ("key", 1u8).serialize(&mut Serializer::new(&mut writer)).unwrap();
+//! let (mut bytes, crc) = writer.finish();
+//! bytes.extend_from_slice(&crc.to_be_bytes());
+//!
+//! let value: (String, u8) = storekey::deserialize(verify(&bytes).unwrap()).unwrap();
+//! assert_eq!(value, ("key".to_owned(), 1));
+//! ```
+//!
+//! The checksum sorts along with the rest of the value, so appending it to a key only preserves
+//! the order of keys which differ before the checksum.
+//!
+//! [`Serializer`]: crate::Serializer
+
+use std::io::{self, Write};
+
+/// The reversed IEEE 802.3 polynomial, as used by zlib, gzip and PNG.
+const POLYNOMIAL: u32 = 0xedb8_8320;
+
+fn update(mut crc: u32, bytes: &[u8]) -> u32 {
+	for b in bytes {
+		crc ^= *b as u32;
+		for _ in 0..8 {
+			crc = (crc >> 1) ^ (POLYNOMIAL & (crc & 1).wrapping_neg());
+		}
+	}
+	crc
+}
+
+/// Returns the CRC-32 checksum of `bytes`.
+pub fn crc32(bytes: &[u8]) -> u32 {
+	!update(!0, bytes)
+}
+
+/// Checks a value ending in the big-endian CRC-32 checksum of the preceding bytes, as produced by
+/// a [`ChecksumWriter`], and returns the value without the checksum.
+///
+/// Returns `None` if the value is shorter than a checksum or the checksum does not match.
+pub fn verify(bytes: &[u8]) -> Option<&[u8]> {
+	let split = bytes.len().checked_sub(4)?;
+	let (value, crc) = bytes.split_at(split);
+	let crc = u32::from_be_bytes(crc.try_into().expect("checksum is four bytes"));
+	(crc32(value) == crc).then_some(value)
+}
+
+/// Writer wrapper which computes the CRC-32 checksum of all bytes written through it.
+#[derive(Debug)]
+pub struct ChecksumWriter<W> {
+	inner: W,
+	crc: u32,
+}
+
+impl<W: Write> ChecksumWriter<W> {
+	/// Creates a new checksumming writer which writes to the provided writer.
+	pub fn new(inner: W) -> Self {
+		ChecksumWriter {
+			inner,
+			crc: !0,
+		}
+	}
+
+	/// Returns the checksum of the bytes written so far.
+	pub fn checksum(&self) -> u32 {
+		!self.crc
+	}
+
+	/// Unwraps this writer, returning the underlying writer and the checksum of the bytes written.
+	pub fn finish(self) -> (W, u32) {
+		let crc = self.checksum();
+		(self.inner, crc)
+	}
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let written = self.inner.write(buf)?;
+		self.crc = update(self.crc, &buf[..written]);
+		Ok(written)
+	}
+
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
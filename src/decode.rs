@@ -1,15 +1,17 @@
 use byteorder::{ReadBytesExt, BE};
 use serde;
-use serde::de::{Deserialize, Visitor};
+use serde::de::{Deserialize, DeserializeOwned, IntoDeserializer, Visitor};
 use std;
 use std::fmt;
 use std::io::{self, BufRead};
 use std::marker::PhantomData;
 use std::str;
-use std::{i16, i32, i64, i8};
 use thiserror::Error;
 
-use self::read::{ReadReader, ReadReference, Reference, SliceReader};
+use self::read::{
+	InvertedReader, ReadReader, ReadReference, Reference, SegmentedReader, SliceReader,
+};
+use crate::{reverse, tagged, trailing};
 
 pub mod read;
 
@@ -17,6 +19,11 @@ pub mod read;
 #[derive(Debug)]
 pub struct Deserializer<R> {
 	reader: R,
+	lossy: bool,
+	/// Whether entries of sequences and maps are preceded by a tag, inside a [`Tagged`](crate::Tagged) value.
+	///
+	/// [`Tagged`]: crate::Tagged
+	tagged: bool,
 }
 
 /// Errors that may be occur when deserializing.
@@ -34,6 +41,60 @@ pub enum Error {
 	Io(#[from] io::Error),
 }
 
+/// Errors compare equal if they are the same variant with equal contents, except that I/O errors
+/// are compared by their [`io::ErrorKind`] only.
+impl PartialEq for Error {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Error::DeserializeAnyUnsupported, Error::DeserializeAnyUnsupported) => true,
+			(Error::UnexpectedEof, Error::UnexpectedEof) => true,
+			(Error::InvalidUtf8, Error::InvalidUtf8) => true,
+			(Error::Message(a), Error::Message(b)) => a == b,
+			(Error::Io(a), Error::Io(b)) => a.kind() == b.kind(),
+			_ => false,
+		}
+	}
+}
+
+/// Errors are converted into I/O errors with a matching kind: I/O errors are unwrapped, truncated
+/// input becomes [`io::ErrorKind::UnexpectedEof`], and anything else becomes
+/// [`io::ErrorKind::InvalidData`].
+impl From<Error> for io::Error {
+	fn from(err: Error) -> io::Error {
+		match err {
+			Error::Io(err) => err,
+			err => io::Error::new(err.io_kind(), err),
+		}
+	}
+}
+
+impl Error {
+	fn io_kind(&self) -> io::ErrorKind {
+		match self {
+			Error::Io(err) => err.kind(),
+			Error::UnexpectedEof => io::ErrorKind::UnexpectedEof,
+			_ => io::ErrorKind::InvalidData,
+		}
+	}
+}
+
+/// An [`Error`] together with the number of bytes consumed before it was detected, returned by
+/// [`deserialize_with_offset`] and [`deserialize_from_with_offset`].
+#[derive(Error, Debug, PartialEq)]
+#[error("{kind} at byte offset {offset}")]
+pub struct DecodeError {
+	pub kind: Error,
+	pub offset: usize,
+}
+
+/// Decode errors are converted into I/O errors like the [`Error`] they contain, keeping the
+/// offset in the message.
+impl From<DecodeError> for io::Error {
+	fn from(err: DecodeError) -> io::Error {
+		io::Error::new(err.kind.io_kind(), err)
+	}
+}
+
 impl serde::de::Error for Error {
 	fn custom<T: fmt::Display>(msg: T) -> Self {
 		Error::Message(msg.to_string())
@@ -44,6 +105,15 @@ impl serde::de::Error for Error {
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Deserialize data from the given slice of bytes.
+///
+/// Strings and byte buffers are stored contiguously, so `&str` and `&[u8]` values, and `Cow`
+/// fields of these types marked `#[serde(borrow)]`, can borrow from `bytes`. No other types can
+/// borrow: integers are stored big-endian, so a `Cow<[u32]>` for example is always deserialized as
+/// owned.
+///
+/// Any buffer which dereferences to `[u8]`, such as a `Vec<u8>` or a memory map, can be passed by
+/// reference. Values which must outlive the buffer should be deserialized as owned types such as
+/// `String`, which never borrow from it.
 pub fn deserialize<'de, T>(bytes: &'de [u8]) -> Result<T>
 where
 	T: Deserialize<'de>,
@@ -52,7 +122,38 @@ where
 	T::deserialize(&mut deserializer)
 }
 
+/// Deserialize data from the given slice of bytes like [`deserialize`], reporting the byte offset
+/// at which any error was detected.
+pub fn deserialize_with_offset<'de, T>(bytes: &'de [u8]) -> std::result::Result<T, DecodeError>
+where
+	T: Deserialize<'de>,
+{
+	let mut deserializer = Deserializer::new(SliceReader::new(bytes));
+	T::deserialize(&mut deserializer).map_err(|kind| DecodeError {
+		kind,
+		offset: deserializer.reader.position(),
+	})
+}
+
+/// Deserialize data from the given slice of bytes, recovering from truncated sequences and maps.
+///
+/// See [`Deserializer::lossy`].
+pub fn deserialize_lossy<'de, T>(bytes: &'de [u8]) -> Result<T>
+where
+	T: Deserialize<'de>,
+{
+	let mut deserializer = Deserializer::new(SliceReader::new(bytes)).lossy();
+	T::deserialize(&mut deserializer)
+}
+
 /// Deserialize data from the given byte reader.
+///
+/// Values can not borrow from a byte reader, since `BufRead` only lends out its buffer until the
+/// next read, so borrowed types such as `&str` fail to deserialize and `Cow` values are always
+/// owned. Strings and byte buffers are read through a single scratch buffer which is reused for
+/// every value, so the only allocations are those made by the deserialized types themselves. For
+/// input which is already in memory, such as the contents of a `Cursor`, use [`deserialize`] on
+/// the underlying slice to borrow instead.
 pub fn deserialize_from<'de, R, T>(reader: R) -> Result<T>
 where
 	R: BufRead,
@@ -62,18 +163,164 @@ where
 	T::deserialize(&mut deserializer)
 }
 
+/// Deserialize data from the given byte reader like [`deserialize_from`], reporting the byte
+/// offset at which any error was detected.
+pub fn deserialize_from_with_offset<'de, R, T>(reader: R) -> std::result::Result<T, DecodeError>
+where
+	R: BufRead,
+	T: Deserialize<'de>,
+{
+	let mut deserializer = Deserializer::new(ReadReader::new(reader));
+	T::deserialize(&mut deserializer).map_err(|kind| DecodeError {
+		kind,
+		offset: deserializer.reader.position(),
+	})
+}
+
+/// Deserialize a stream of concatenated values from the given byte reader.
+///
+/// The returned iterator yields values until the reader is exhausted. If the end of the reader is
+/// reached part way through a value, the iterator yields the resulting error and then stops.
+pub fn deserialize_iter<R, T>(reader: R) -> DeserializeIter<R, T>
+where
+	R: BufRead,
+	T: DeserializeOwned,
+{
+	DeserializeIter {
+		deserializer: Deserializer::new(ReadReader::new(reader)),
+		done: false,
+		_marker: PhantomData,
+	}
+}
+
+/// An iterator over values deserialized from a byte reader, created by [`deserialize_iter`].
+#[derive(Debug)]
+pub struct DeserializeIter<R: BufRead, T> {
+	deserializer: Deserializer<ReadReader<R>>,
+	done: bool,
+	_marker: PhantomData<fn() -> T>,
+}
+
+impl<R: BufRead, T> DeserializeIter<R, T> {
+	/// Returns the number of bytes consumed so far, which after an error is the byte offset at
+	/// which it was detected.
+	pub fn offset(&self) -> usize {
+		self.deserializer.reader.position()
+	}
+}
+
+impl<R, T> Iterator for DeserializeIter<R, T>
+where
+	R: BufRead,
+	T: DeserializeOwned,
+{
+	type Item = Result<T>;
+
+	fn next(&mut self) -> Option<Result<T>> {
+		if self.done {
+			return None;
+		}
+		let result = match self.deserializer.reader.fill_buf() {
+			Ok([]) => {
+				self.done = true;
+				return None;
+			}
+			Ok(_) => T::deserialize(&mut self.deserializer),
+			Err(err) => Err(err.into()),
+		};
+		self.done = result.is_err();
+		Some(result)
+	}
+}
+
+/// A saved position of a [`Deserializer`] reading from a slice, created by
+/// [`Deserializer::checkpoint`].
+#[derive(Clone, Debug)]
+pub struct Checkpoint<'de>(SliceReader<'de>);
+
+impl<'de> Deserializer<SliceReader<'de>> {
+	/// Creates a new ordered bytes decoder which reads from the provided slice.
+	pub fn from_slice(bytes: &'de [u8]) -> Self {
+		Deserializer::new(SliceReader::new(bytes))
+	}
+
+	/// Returns the number of bytes of input which have not been read yet.
+	pub fn remaining(&self) -> usize {
+		self.reader.remaining().len()
+	}
+
+	/// Returns the input which has not been read yet, borrowed for the lifetime of the input.
+	///
+	/// This makes it possible to treat the rest of the input as a single value, such as a trailing
+	/// unframed payload after a key.
+	pub fn as_slice(&self) -> &'de [u8] {
+		self.reader.remaining()
+	}
+
+	/// Saves the current position in the input, so that it can be returned to with
+	/// [`Deserializer::restore`].
+	pub fn checkpoint(&self) -> Checkpoint<'de> {
+		Checkpoint(self.reader.clone())
+	}
+
+	/// Returns to a position previously saved with [`Deserializer::checkpoint`], for example to
+	/// retry deserializing the same input as a different type after an error.
+	pub fn restore(&mut self, checkpoint: Checkpoint<'de>) {
+		self.reader = checkpoint.0;
+	}
+}
+
+impl<'de> Deserializer<SegmentedReader<'de>> {
+	/// Creates a new ordered bytes decoder which reads from the provided segments, as if they were
+	/// a single contiguous slice.
+	///
+	/// Borrowed strings and byte slices can be deserialized as long as they do not span a segment
+	/// boundary; for values which may do so, use a `Cow` instead.
+	pub fn from_segments(segments: &'de [&'de [u8]]) -> Self {
+		Deserializer::new(SegmentedReader::new(segments))
+	}
+}
+
+impl<R: BufRead> Deserializer<ReadReader<R>> {
+	/// Creates a new ordered bytes decoder which reads from the provided byte reader.
+	pub fn from_reader(reader: R) -> Self {
+		Deserializer::new(ReadReader::new(reader))
+	}
+}
+
 impl<'de, R: ReadReference<'de>> Deserializer<R> {
 	/// Creates a new ordered bytes encoder whose output will be written to the provided writer.
 	pub fn new(reader: R) -> Deserializer<R> {
 		Deserializer {
 			reader,
+			lossy: false,
+			tagged: false,
 		}
 	}
 
+	/// Makes this deserializer recover from input which is truncated part way through a sequence
+	/// or map, by ending the sequence or map at the end of the input instead of failing.
+	///
+	/// A map entry truncated part way through its value is dropped along with its key when the
+	/// map is deserialized with `MapAccess::next_entry`, as the standard map types are. Visitors
+	/// which read keys and values separately still fail on such an entry.
+	///
+	/// This is intended for recovering data from corrupt input, and should not be used otherwise,
+	/// since a truncated value can not be distinguished from a complete one.
+	pub fn lossy(mut self) -> Self {
+		self.lossy = true;
+		self
+	}
+
+	/// Consumes the terminator of a sequence or map if it is the next byte, returning whether it
+	/// was.
+	#[deprecated(
+		note = "this does not understand the entry tags inside `Tagged` values, and a sequence entry \
+		        which starts with `0x01` is mistaken for the terminator"
+	)]
 	pub fn move_on(&mut self) -> Result<bool> {
-		let buf = self.reader.fill_buf()?;
-		match buf.first() {
-			Some(v) if v == &0x01 => {
+		match self.peek_u8()? {
+			Some(0x01) => {
 				self.reader.consume(1);
 				Ok(true)
 			}
@@ -81,6 +328,106 @@ impl<'de, R: ReadReference<'de>> Deserializer<R> {
 		}
 	}
 
+	/// Reads the terminator or, inside a [`Tagged`](crate::Tagged) value, the tag preceding each
+	/// sequence or map entry, returning whether an entry follows.
+	fn next_entry(&mut self) -> Result<bool> {
+		match self.peek_u8()? {
+			None if self.lossy => Ok(false),
+			None => Err(Error::UnexpectedEof),
+			Some(0x01) => {
+				self.reader.consume(1);
+				Ok(false)
+			}
+			Some(_) if !self.tagged => Ok(true),
+			Some(0x02) => {
+				self.reader.consume(1);
+				Ok(true)
+			}
+			Some(b) => {
+				let msg = format!("expected `1` or `2` for sequence tag - found {}", b);
+				Err(Error::Message(msg))
+			}
+		}
+	}
+
+	/// Fails if an untagged sequence or map entry which started at `start` consumed no input,
+	/// since the same entry would otherwise be read again and again.
+	fn check_progress(&self, start: Option<usize>) -> Result<()> {
+		if !self.tagged && start.is_some() && self.reader.consumed() == start {
+			return Err(Error::Message("sequence entry consumed no input".to_owned()));
+		}
+		Ok(())
+	}
+
+	/// Deserializes the seconds and nanoseconds of a `Duration`, failing if the nanoseconds are a
+	/// second or more.
+	fn deserialize_duration<V>(&mut self, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		struct Access<'de, 'a, R>
+		where
+			R: 'a + ReadReference<'de>,
+		{
+			deserializer: &'a mut Deserializer<R>,
+			field: usize,
+			_spooky: PhantomData<&'de ()>,
+		}
+
+		impl<'de, 'a, R> serde::de::SeqAccess<'de> for Access<'de, 'a, R>
+		where
+			R: ReadReference<'de>,
+		{
+			type Error = Error;
+
+			fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+			where
+				T: serde::de::DeserializeSeed<'de>,
+			{
+				self.field += 1;
+				match self.field {
+					1 => seed.deserialize(&mut *self.deserializer).map(Some),
+					2 => {
+						let nanos = self.deserializer.reader.read_u32::<BE>()?;
+						if nanos >= 1_000_000_000 {
+							let msg =
+								format!("Duration nanoseconds out of range - found {}", nanos);
+							return Err(Error::Message(msg));
+						}
+						seed.deserialize(nanos.into_deserializer()).map(Some)
+					}
+					_ => Ok(None),
+				}
+			}
+
+			fn size_hint(&self) -> Option<usize> {
+				Some(2 - self.field.min(2))
+			}
+		}
+
+		visitor.visit_seq(Access {
+			deserializer: self,
+			field: 0,
+			_spooky: PhantomData,
+		})
+	}
+
+	/// Deserialize exactly `n` consecutive values, which were serialized one after another without
+	/// a terminator, in the same way as the elements of a tuple or fixed-size array.
+	///
+	/// This is intended for sections whose length is known from a previously deserialized value.
+	pub fn deserialize_n<T>(&mut self, n: usize) -> Result<Vec<T>>
+	where
+		T: Deserialize<'de>,
+	{
+		(0..n).map(|_| T::deserialize(&mut *self)).collect()
+	}
+
+	/// Returns the next byte of input without consuming it, or `None` at the end of the input.
+	pub fn peek_u8(&mut self) -> Result<Option<u8>> {
+		Ok(self.reader.fill_buf()?.first().copied())
+	}
+
 	/// Deserialize a `u64` that has been serialized using the `serialize_var_u64` method.
 	pub fn deserialize_var_u64(&mut self) -> Result<u64> {
 		let header = self.reader.read_u8()?;
@@ -109,7 +456,7 @@ impl<'de, R: ReadReference<'de>> Deserializer<R> {
 	}
 }
 
-impl<'de, 'a, R> serde::de::Deserializer<'de> for &'a mut Deserializer<R>
+impl<'de, R> serde::de::Deserializer<'de> for &mut Deserializer<R>
 where
 	R: ReadReference<'de>,
 {
@@ -166,6 +513,14 @@ where
 		visitor.visit_i64(i ^ i64::MIN)
 	}
 
+	fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		let i = self.reader.read_i128::<BE>()?;
+		visitor.visit_i128(i ^ i128::MIN)
+	}
+
 	fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value>
 	where
 		V: Visitor<'de>,
@@ -198,6 +553,14 @@ where
 		visitor.visit_u64(u)
 	}
 
+	fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		let u = self.reader.read_u128::<BE>()?;
+		visitor.visit_u128(u)
+	}
+
 	fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value>
 	where
 		V: Visitor<'de>,
@@ -232,7 +595,7 @@ where
 				let c = string.chars().next().ok_or(Error::InvalidUtf8)?;
 				visitor.visit_char(c)
 			}
-			Err(_) => return Err(Error::UnexpectedEof),
+			Err(_) => Err(Error::UnexpectedEof),
 		}
 	}
 
@@ -251,7 +614,7 @@ where
 					visitor.visit_str(string)
 				}
 			},
-			Err(_) => return Err(Error::UnexpectedEof),
+			Err(_) => Err(Error::UnexpectedEof),
 		}
 	}
 
@@ -313,10 +676,27 @@ where
 		visitor.visit_unit()
 	}
 
-	fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+	fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
 	where
 		V: Visitor<'de>,
 	{
+		if name == reverse::TOKEN {
+			let mut inverted = Deserializer {
+				reader: InvertedReader::new(&mut self.reader),
+				lossy: self.lossy,
+				tagged: self.tagged,
+			};
+			return visitor.visit_newtype_struct(&mut inverted);
+		}
+		if name == tagged::TOKEN {
+			let tagged = std::mem::replace(&mut self.tagged, true);
+			let result = visitor.visit_newtype_struct(&mut *self);
+			self.tagged = tagged;
+			return result;
+		}
+		if name == trailing::TOKEN && self.peek_u8()?.is_none() {
+			return visitor.visit_none();
+		}
 		visitor.visit_newtype_struct(self)
 	}
 
@@ -342,12 +722,17 @@ where
 			where
 				T: serde::de::DeserializeSeed<'de>,
 			{
-				if self.deserializer.move_on()? {
+				if !self.deserializer.next_entry()? {
 					return Ok(None);
 				}
+				let start = self.deserializer.reader.consumed();
 				match serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer) {
-					Ok(v) => Ok(Some(v)),
-					Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+					Ok(value) => {
+						self.deserializer.check_progress(start)?;
+						Ok(Some(value))
+					}
+					// A partially read element at the end of the input is dropped in lossy mode.
+					Err(_) if self.deserializer.lossy && self.deserializer.peek_u8()?.is_none() => {
 						Ok(None)
 					}
 					Err(err) => Err(err),
@@ -425,6 +810,8 @@ where
 			R: 'a + ReadReference<'de>,
 		{
 			deserializer: &'a mut Deserializer<R>,
+			/// Reader position at the start of the current entry.
+			start: Option<usize>,
 			_spooky: PhantomData<&'de ()>,
 		}
 
@@ -438,12 +825,14 @@ where
 			where
 				T: serde::de::DeserializeSeed<'de>,
 			{
-				if self.deserializer.move_on()? {
+				if !self.deserializer.next_entry()? {
 					return Ok(None);
 				}
+				self.start = self.deserializer.reader.consumed();
 				match serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer) {
-					Ok(v) => Ok(Some(v)),
-					Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+					Ok(key) => Ok(Some(key)),
+					// A partially read key at the end of the input is dropped in lossy mode.
+					Err(_) if self.deserializer.lossy && self.deserializer.peek_u8()?.is_none() => {
 						Ok(None)
 					}
 					Err(err) => Err(err),
@@ -454,25 +843,56 @@ where
 			where
 				T: serde::de::DeserializeSeed<'de>,
 			{
-				serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer)
+				let value = serde::de::DeserializeSeed::deserialize(seed, &mut *self.deserializer)?;
+				self.deserializer.check_progress(self.start)?;
+				Ok(value)
+			}
+
+			fn next_entry_seed<K, V>(
+				&mut self,
+				kseed: K,
+				vseed: V,
+			) -> Result<Option<(K::Value, V::Value)>>
+			where
+				K: serde::de::DeserializeSeed<'de>,
+				V: serde::de::DeserializeSeed<'de>,
+			{
+				let Some(key) = self.next_key_seed(kseed)? else {
+					return Ok(None);
+				};
+				match self.next_value_seed(vseed) {
+					Ok(value) => Ok(Some((key, value))),
+					// An entry whose value is partially read at the end of the input is dropped
+					// along with its key in lossy mode.
+					Err(_) if self.deserializer.lossy && self.deserializer.peek_u8()?.is_none() => {
+						Ok(None)
+					}
+					Err(err) => Err(err),
+				}
 			}
 		}
 
 		visitor.visit_map(Access {
 			deserializer: self,
+			start: None,
 			_spooky: PhantomData,
 		})
 	}
 
 	fn deserialize_struct<V>(
 		self,
-		_name: &'static str,
+		name: &'static str,
 		fields: &'static [&'static str],
 		visitor: V,
 	) -> Result<V::Value>
 	where
 		V: Visitor<'de>,
 	{
+		// serde carries nanoseconds of a second or more over into the seconds of a `Duration`, so
+		// out of range nanoseconds are rejected here instead.
+		if name == "Duration" && fields == ["secs", "nanos"] {
+			return self.deserialize_duration(visitor);
+		}
 		self.deserialize_tuple(fields.len(), visitor)
 	}
 
@@ -485,74 +905,69 @@ where
 	where
 		V: Visitor<'de>,
 	{
-		impl<'de, 'a, R> serde::de::EnumAccess<'de> for &'a mut Deserializer<R>
-		where
-			R: ReadReference<'de>,
-		{
-			type Error = Error;
-			type Variant = Self;
+		visitor.visit_enum(self)
+	}
 
-			fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
-			where
-				V: serde::de::DeserializeSeed<'de>,
-			{
-				let idx: u32 = serde::de::Deserialize::deserialize(&mut *self)?;
-				let val: Result<_> =
-					seed.deserialize(serde::de::IntoDeserializer::into_deserializer(idx));
-				Ok((val?, self))
-			}
-		}
+	fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value>
+	where
+		V: serde::de::Visitor<'de>,
+	{
+		Err(Error::DeserializeAnyUnsupported)
+	}
 
-		impl<'de, 'a, R> serde::de::VariantAccess<'de> for &'a mut Deserializer<R>
-		where
-			R: ReadReference<'de>,
-		{
-			type Error = Error;
+	fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
+	where
+		V: serde::de::Visitor<'de>,
+	{
+		Err(Error::DeserializeAnyUnsupported)
+	}
+}
 
-			fn unit_variant(self) -> Result<()> {
-				Ok(())
-			}
+impl<'de, R> serde::de::EnumAccess<'de> for &mut Deserializer<R>
+where
+	R: ReadReference<'de>,
+{
+	type Error = Error;
+	type Variant = Self;
 
-			fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
-			where
-				T: serde::de::DeserializeSeed<'de>,
-			{
-				serde::de::DeserializeSeed::deserialize(seed, self)
-			}
+	fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant)>
+	where
+		V: serde::de::DeserializeSeed<'de>,
+	{
+		let idx: u32 = serde::de::Deserialize::deserialize(&mut *self)?;
+		let val: Result<_> = seed.deserialize(serde::de::IntoDeserializer::into_deserializer(idx));
+		Ok((val?, self))
+	}
+}
 
-			fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
-			where
-				V: serde::de::Visitor<'de>,
-			{
-				serde::de::Deserializer::deserialize_tuple(self, len, visitor)
-			}
+impl<'de, R> serde::de::VariantAccess<'de> for &mut Deserializer<R>
+where
+	R: ReadReference<'de>,
+{
+	type Error = Error;
 
-			fn struct_variant<V>(
-				self,
-				fields: &'static [&'static str],
-				visitor: V,
-			) -> Result<V::Value>
-			where
-				V: serde::de::Visitor<'de>,
-			{
-				serde::de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
-			}
-		}
+	fn unit_variant(self) -> Result<()> {
+		Ok(())
+	}
 
-		visitor.visit_enum(self)
+	fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value>
+	where
+		T: serde::de::DeserializeSeed<'de>,
+	{
+		serde::de::DeserializeSeed::deserialize(seed, self)
 	}
 
-	fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value>
+	fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value>
 	where
 		V: serde::de::Visitor<'de>,
 	{
-		Err(Error::DeserializeAnyUnsupported)
+		serde::de::Deserializer::deserialize_tuple(self, len, visitor)
 	}
 
-	fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value>
+	fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
 	where
 		V: serde::de::Visitor<'de>,
 	{
-		Err(Error::DeserializeAnyUnsupported)
+		serde::de::Deserializer::deserialize_tuple(self, fields.len(), visitor)
 	}
 }
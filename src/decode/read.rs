@@ -18,28 +18,51 @@ pub trait ReadReference<'de>: Read + BufRead {
 		&'a mut self,
 		delimiter: u8,
 	) -> Result<Reference<'de, 'a>, io::Error>;
+
+	/// Returns the number of bytes consumed so far, if the reader keeps count.
+	#[inline]
+	fn consumed(&self) -> Option<usize> {
+		None
+	}
 }
 
+/// Buffered reader wrapper.
+///
+/// All references it returns are copied into a single internal buffer, which is reused for every
+/// read. They can not borrow from the input instead, because [`BufRead::fill_buf`] only lends out
+/// the reader's buffer until the next call to [`BufRead::consume`], so nothing read from it can
+/// outlive the next read. Use [`deserialize`](crate::deserialize) or
+/// [`Deserializer::from_slice`](crate::Deserializer::from_slice) on in-memory input to borrow.
 #[derive(Debug)]
-pub(crate) struct ReadReader<R: Read> {
+pub struct ReadReader<R: Read> {
 	inner: R,
 	buf: Vec<u8>,
+	/// Number of bytes consumed so far.
+	position: usize,
 }
 
 impl<R: Read> ReadReader<R> {
 	#[inline]
-	pub(crate) fn new(inner: R) -> Self {
+	pub fn new(inner: R) -> Self {
 		ReadReader {
 			inner,
 			buf: Vec::with_capacity(128),
+			position: 0,
 		}
 	}
+
+	/// Returns the number of bytes consumed so far.
+	#[inline]
+	pub(crate) fn position(&self) -> usize {
+		self.position
+	}
 }
 
 impl<'de, R: BufRead> ReadReference<'de> for ReadReader<R> {
 	fn read_reference<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a>, io::Error> {
 		self.buf.clear();
 		let read = self.inner.by_ref().take(len as u64).read_to_end(&mut self.buf)?;
+		self.position += read;
 		if read != len {
 			return Err(io::ErrorKind::UnexpectedEof.into());
 		}
@@ -52,21 +75,29 @@ impl<'de, R: BufRead> ReadReference<'de> for ReadReader<R> {
 		delimiter: u8,
 	) -> Result<Reference<'de, 'a>, io::Error> {
 		self.buf.clear();
-		self.inner.read_until(delimiter, &mut self.buf)?;
-		Ok(Reference::Copied(&self.buf[0..self.buf.len().saturating_sub(1)]))
+		self.position += self.inner.read_until(delimiter, &mut self.buf)?;
+		match self.buf.pop() {
+			Some(b) if b == delimiter => Ok(Reference::Copied(&self.buf)),
+			_ => Err(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF")),
+		}
+	}
+
+	#[inline]
+	fn consumed(&self) -> Option<usize> {
+		Some(self.position)
 	}
 }
 
 impl<R: Read> Read for ReadReader<R> {
 	#[inline]
 	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
-		self.inner.read(buf)
+		let read = self.inner.read(buf)?;
+		self.position += read;
+		Ok(read)
 	}
 
-	#[inline]
-	fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
-		self.inner.read_exact(buf)
-	}
+	// `read_exact` is left to the default implementation, which loops over `read` and so counts
+	// the bytes consumed before a short read as well.
 }
 
 impl<R: BufRead> BufRead for ReadReader<R> {
@@ -75,19 +106,24 @@ impl<R: BufRead> BufRead for ReadReader<R> {
 	}
 
 	fn consume(&mut self, amt: usize) {
+		self.position += amt;
 		self.inner.consume(amt);
 	}
 
 	fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize> {
-		self.inner.read_until(byte, buf)
+		let read = self.inner.read_until(byte, buf)?;
+		self.position += read;
+		Ok(read)
 	}
 }
 
 /// Borrowed reader wrapper.
-#[derive(Debug)]
-pub(crate) struct SliceReader<'a> {
+#[derive(Clone, Debug)]
+pub struct SliceReader<'a> {
 	/// Haven't read yet.
 	inner: &'a [u8],
+	/// Length of the original slice.
+	len: usize,
 }
 
 impl<'a> SliceReader<'a> {
@@ -95,8 +131,21 @@ impl<'a> SliceReader<'a> {
 	pub fn new(inner: &'a [u8]) -> Self {
 		Self {
 			inner,
+			len: inner.len(),
 		}
 	}
+
+	/// Returns the number of bytes consumed so far.
+	#[inline]
+	pub(crate) fn position(&self) -> usize {
+		self.len - self.inner.len()
+	}
+
+	/// Returns the bytes which have not been consumed yet.
+	#[inline]
+	pub(crate) fn remaining(&self) -> &'a [u8] {
+		self.inner
+	}
 }
 
 impl<'a> Read for SliceReader<'a> {
@@ -141,7 +190,252 @@ impl<'de> ReadReference<'de> for SliceReader<'de> {
 			self.inner = &after[1..];
 			Ok(Reference::Borrowed(before))
 		} else {
+			self.inner = &[];
 			Err(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"))
 		}
 	}
+
+	#[inline]
+	fn consumed(&self) -> Option<usize> {
+		Some(self.position())
+	}
+}
+
+/// Borrowed reader over a list of slices, which are read as if they were a single contiguous
+/// slice.
+///
+/// Values that lie entirely within one segment are borrowed. Values that span a segment boundary
+/// are copied.
+#[derive(Debug)]
+pub struct SegmentedReader<'a> {
+	/// Haven't read yet, in the current segment.
+	current: &'a [u8],
+	/// Segments after the current one.
+	rest: &'a [&'a [u8]],
+	buf: Vec<u8>,
+	/// Number of bytes consumed so far.
+	position: usize,
+}
+
+impl<'a> SegmentedReader<'a> {
+	#[inline]
+	pub fn new(segments: &'a [&'a [u8]]) -> Self {
+		SegmentedReader {
+			current: &[],
+			rest: segments,
+			buf: Vec::new(),
+			position: 0,
+		}
+	}
+
+	/// Skips over exhausted segments, so that `current` is only empty at the end of the input.
+	fn advance(&mut self) {
+		while self.current.is_empty() {
+			match self.rest.split_first() {
+				Some((first, rest)) => {
+					self.current = first;
+					self.rest = rest;
+				}
+				None => break,
+			}
+		}
+	}
+}
+
+impl<'a> Read for SegmentedReader<'a> {
+	#[inline]
+	fn read(&mut self, buf: &mut [u8]) -> Result<usize, io::Error> {
+		self.advance();
+		let read = self.current.read(buf)?;
+		self.position += read;
+		Ok(read)
+	}
+}
+
+impl<'a> BufRead for SegmentedReader<'a> {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		self.advance();
+		Ok(self.current)
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.current = &self.current[amt..];
+		self.position += amt;
+	}
+}
+
+impl<'de> ReadReference<'de> for SegmentedReader<'de> {
+	fn read_reference<'a>(&'a mut self, len: usize) -> Result<Reference<'de, 'a>, io::Error> {
+		self.advance();
+		if len <= self.current.len() {
+			let (a, b) = self.current.split_at(len);
+			self.current = b;
+			self.position += len;
+			return Ok(Reference::Borrowed(a));
+		}
+		self.buf.clear();
+		while self.buf.len() < len {
+			self.advance();
+			if self.current.is_empty() {
+				return Err(ErrorKind::UnexpectedEof.into());
+			}
+			let (a, b) = self.current.split_at(self.current.len().min(len - self.buf.len()));
+			self.buf.extend_from_slice(a);
+			self.current = b;
+		}
+		self.position += len;
+		Ok(Reference::Copied(&self.buf))
+	}
+
+	fn read_reference_until<'a>(
+		&'a mut self,
+		delimiter: u8,
+	) -> Result<Reference<'de, 'a>, io::Error> {
+		self.advance();
+		if let Some(end) = memchr::memchr(delimiter, self.current) {
+			let (before, after) = self.current.split_at(end);
+			self.current = &after[1..];
+			self.position += end + 1;
+			return Ok(Reference::Borrowed(before));
+		}
+		self.buf.clear();
+		loop {
+			self.advance();
+			if self.current.is_empty() {
+				return Err(io::Error::new(ErrorKind::UnexpectedEof, "unexpected EOF"));
+			}
+			if let Some(end) = memchr::memchr(delimiter, self.current) {
+				self.buf.extend_from_slice(&self.current[..end]);
+				self.current = &self.current[end + 1..];
+				self.position += self.buf.len() + 1;
+				return Ok(Reference::Copied(&self.buf));
+			}
+			self.buf.extend_from_slice(self.current);
+			self.current = &[];
+		}
+	}
+
+	#[inline]
+	fn consumed(&self) -> Option<usize> {
+		Some(self.position)
+	}
+}
+
+/// Reader wrapper which complements every byte read from the inner reader.
+///
+/// The inner reader is held as a trait object, so that nested reversed values do not produce an
+/// ever growing chain of reader types.
+pub(crate) struct InvertedReader<'r, 'de> {
+	inner: &'r mut dyn ReadReference<'de>,
+	/// Complement of the bytes at the front of the inner reader, which have not been consumed
+	/// from it yet.
+	peeked: Vec<u8>,
+	/// Number of bytes at the start of `peeked` which have already been consumed.
+	consumed: usize,
+	/// Scratch buffer for references which do not fit in `peeked`.
+	buf: Vec<u8>,
+}
+
+/// Maximum number of bytes complemented ahead of the deserializer, so that a short reversed value
+/// at the start of a long input does not complement the whole input.
+const PEEK_LEN: usize = 256;
+
+impl<'r, 'de> InvertedReader<'r, 'de> {
+	#[inline]
+	pub(crate) fn new(inner: &'r mut dyn ReadReference<'de>) -> Self {
+		InvertedReader {
+			inner,
+			peeked: Vec::new(),
+			consumed: 0,
+			buf: Vec::new(),
+		}
+	}
+
+	/// Returns the complemented bytes which are buffered but not yet consumed.
+	#[inline]
+	fn peeked(&self) -> &[u8] {
+		&self.peeked[self.consumed..]
+	}
+
+	/// Forgets the buffered bytes, before reading past them from the inner reader.
+	#[inline]
+	fn discard_peeked(&mut self) {
+		self.peeked.clear();
+		self.consumed = 0;
+	}
+}
+
+impl<'r, 'de> Read for InvertedReader<'r, 'de> {
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+		if !self.peeked().is_empty() {
+			let read = self.peeked().len().min(buf.len());
+			buf[..read].copy_from_slice(&self.peeked()[..read]);
+			self.consume(read);
+			return Ok(read);
+		}
+		let read = self.inner.read(buf)?;
+		buf[..read].iter_mut().for_each(|b| *b = !*b);
+		Ok(read)
+	}
+}
+
+impl<'r, 'de> BufRead for InvertedReader<'r, 'de> {
+	fn fill_buf(&mut self) -> io::Result<&[u8]> {
+		if self.peeked().is_empty() {
+			let available = self.inner.fill_buf()?;
+			let available = &available[..available.len().min(PEEK_LEN)];
+			self.peeked.clear();
+			self.peeked.extend(available.iter().map(|b| !b));
+			self.consumed = 0;
+		}
+		Ok(self.peeked())
+	}
+
+	fn consume(&mut self, amt: usize) {
+		self.consumed = (self.consumed + amt).min(self.peeked.len());
+		self.inner.consume(amt);
+	}
+}
+
+impl<'r, 'de, 'x> ReadReference<'x> for InvertedReader<'r, 'de> {
+	fn read_reference<'a>(&'a mut self, len: usize) -> Result<Reference<'x, 'a>, io::Error> {
+		if len <= self.peeked().len() {
+			let start = self.consumed;
+			self.consume(len);
+			return Ok(Reference::Copied(&self.peeked[start..start + len]));
+		}
+		self.discard_peeked();
+		let bytes = self.inner.read_reference(len)?;
+		invert_into(&mut self.buf, bytes);
+		Ok(Reference::Copied(&self.buf))
+	}
+
+	fn read_reference_until<'a>(
+		&'a mut self,
+		delimiter: u8,
+	) -> Result<Reference<'x, 'a>, io::Error> {
+		if let Some(end) = memchr::memchr(delimiter, self.peeked()) {
+			let start = self.consumed;
+			self.consume(end + 1);
+			return Ok(Reference::Copied(&self.peeked[start..start + end]));
+		}
+		self.discard_peeked();
+		let bytes = self.inner.read_reference_until(!delimiter)?;
+		invert_into(&mut self.buf, bytes);
+		Ok(Reference::Copied(&self.buf))
+	}
+
+	#[inline]
+	fn consumed(&self) -> Option<usize> {
+		self.inner.consumed()
+	}
+}
+
+fn invert_into(buf: &mut Vec<u8>, bytes: Reference<'_, '_>) {
+	let bytes = match bytes {
+		Reference::Borrowed(b) => b,
+		Reference::Copied(b) => b,
+	};
+	buf.clear();
+	buf.extend(bytes.iter().map(|b| !b));
 }
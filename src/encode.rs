@@ -1,8 +1,9 @@
+use crate::{reverse, tagged};
 use byteorder::{WriteBytesExt, BE};
 use serde::{self, Serialize};
+use std::cmp::Ordering;
 use std::fmt;
 use std::io::{self, Write};
-use std::{self, i16, i32, i64, i8};
 use thiserror::Error;
 
 /// A serializer for a byte format that preserves lexicographic sort order.
@@ -17,9 +18,9 @@ use thiserror::Error;
 ///
 /// ##### Unsigned Integers
 ///
-/// `u8`, `u16`, `u32`, and `u64` are serialized into 1, 2, 4, and 8 bytes of output, respectively.
-/// Order is preserved by encoding the bytes in big-endian (most-significant bytes first) format.
-/// `usize` is always serialized as if it were `u64`.
+/// `u8`, `u16`, `u32`, `u64`, and `u128` are serialized into 1, 2, 4, 8, and 16 bytes of output,
+/// respectively. Order is preserved by encoding the bytes in big-endian (most-significant bytes
+/// first) format. `usize` is always serialized as if it were `u64`.
 ///
 /// The `Serializer` also supports variable-length serialization of unsigned integers via the
 /// `serialize_var_u64` method. Smaller magnitude values (closer to 0) will encode into fewer
@@ -27,9 +28,13 @@ use thiserror::Error;
 ///
 /// ##### Signed Integers
 ///
-/// `i8`, `i16`, `i32`, and `i64` are encoded into 1, 2, 4, and 8 bytes of output, respectively.
-/// Order is preserved by taking the bitwise complement of the value, and encoding the resulting
-/// bytes in big-endian format. `isize` is always serialized as if it were `i64`.
+/// `i8`, `i16`, `i32`, `i64`, and `i128` are encoded into 1, 2, 4, 8, and 16 bytes of output,
+/// respectively. Order is preserved by flipping the sign bit of the value, and encoding the
+/// resulting bytes in big-endian format. `isize` is always serialized as if it were `i64`.
+///
+/// Because of the flipped sign bit, signed and unsigned integers of the same width do not encode
+/// alike: `5i32` and `5u32` serialize to different bytes, and keys mixing the two do not sort
+/// meaningfully. Only values of the same type should be compared.
 ///
 /// The `Serializer` also supports variable-length serialization of signed integers via the
 /// `serialize_var_i64` method. Smaller magnitude values (closer to 0) will encode into fewer
@@ -44,17 +49,21 @@ use thiserror::Error;
 /// typically hard or impossible to use an approximate 'epsilon' approach when using keys for
 /// lookup.
 ///
+/// NaNs are encoded with their sign and payload bits, so different NaNs encode differently. Use
+/// [`Serializer::canonical_nan`] to encode every NaN identically.
+///
 /// [`f64::total_cmp`]: https://doc.rust-lang.org/std/primitive.f64.html#method.total_cmp
 ///
 /// ##### Characters
 ///
-/// Characters are serialized into between 1 and 4 bytes of output. The resulting length is
-/// equivalent to the result of `char::len_utf8`.
+/// Characters are serialized like single character strings: their UTF8 representation, between 1
+/// and 4 bytes as given by `char::len_utf8`, followed by a NUL terminator. Because UTF8 preserves
+/// code point order, characters sort by code point. The NUL char itself can not be serialized.
 ///
 /// ##### Booleans
 ///
-/// Booleans are serialized into a single byte of output. `false` values will sort before `true`
-/// values.
+/// Booleans are serialized into a single byte of output, `0` for `false` and `1` for `true`, so
+/// `false` values will sort before `true` values.
 ///
 /// ##### Options
 ///
@@ -71,19 +80,107 @@ use thiserror::Error;
 /// Enums are encoded with a `u32` variant index tag, plus the consituent fields in the case of an
 /// enum-struct.
 ///
+/// ##### Network Addresses
+///
+/// `Ipv4Addr` and `Ipv6Addr` are serialized as their 4 and 16 octets respectively, so they sort
+/// in the same order as the addresses themselves. `IpAddr` is serialized as an enum, so all
+/// `IpAddr::V4` values sort before all `IpAddr::V6` values.
+///
+/// `SocketAddrV4` and `SocketAddrV6` are serialized as their address followed by their `u16`
+/// port, so they sort by address and then by port. The flow info and scope ID of a `SocketAddrV6`
+/// are not serialized, and are zero once deserialized. `SocketAddr` is serialized as an enum in
+/// the same way as `IpAddr`.
+///
+/// ##### Durations
+///
+/// `Duration` is serialized as a struct of its `u64` seconds and `u32` subsecond nanoseconds, so
+/// durations sort by length. When deserializing, nanoseconds of a second or more are rejected
+/// rather than carried over into the seconds. This applies to any struct named `Duration` whose
+/// fields are `secs` and `nanos`.
+///
 /// ##### Sequences, Strings and Maps
 ///
 /// Sequences are ordered from the most significant to the least. Strings are serialized into their
-/// natural UTF8 representation.
+/// natural UTF8 representation, followed by a NUL terminator. NUL chars inside strings are not
+/// escaped, so strings containing them do not deserialize correctly.
 ///
+/// The elements of a sequence are written one after another and followed by a `0x01` terminator.
 /// The ordering of sequential elements follows the `Ord` implementation of `slice`, that is, from
-/// left to write when viewing a `Vec` printed via the `{:?}` formatter.
+/// left to write when viewing a `Vec` printed via the `{:?}` formatter. An element whose encoding
+/// starts with `0x01`, such as an empty inner `Vec`, `Some(_)` or the `u8` value `1`, is read back
+/// as the end of the sequence, and sequences of zero-width elements such as `()` do not encode
+/// their length. Wrap such values in [`Tagged`](crate::Tagged), which precedes every element with
+/// a `0x02` tag instead.
+///
+/// Byte buffers which serialize themselves as bytes rather than as a sequence, such as
+/// `serde_bytes::ByteBuf` and `CString`, are serialized as a `u64` length followed by the raw
+/// bytes. They therefore sort by length first, and only then by content.
+///
+/// `OsString` uses serde's platform-specific representation: an enum of raw bytes on Unix and
+/// UTF-16 code units on Windows. Values serialized on different platforms therefore neither sort
+/// together nor deserialize on the other platform. `Path` and `PathBuf` are serialized as strings,
+/// exactly as given and without normalisation, and fail to serialize if not valid UTF-8.
+///
+/// Sets and maps are serialized as sequences in iteration order. For `BTreeSet` and `BTreeMap`
+/// this is key order, so the encodings sort like the collections themselves. `HashSet` and
+/// `HashMap` iterate in an unspecified order, so equal collections may serialize to different
+/// bytes; they should not be used in keys.
 #[derive(Debug)]
 pub struct Serializer<W>
 where
 	W: Write,
 {
-	writer: W,
+	writer: CountingWriter<W>,
+	canonical_nan: bool,
+	/// Whether entries of sequences and maps are preceded by a tag, inside a [`Tagged`](crate::Tagged) value.
+	///
+	/// [`Tagged`]: crate::Tagged
+	tagged: bool,
+}
+
+/// Writer wrapper which keeps track of the number of bytes written.
+#[derive(Debug)]
+struct CountingWriter<W> {
+	inner: W,
+	count: usize,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+	#[inline]
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let written = self.inner.write(buf)?;
+		self.count += written;
+		Ok(written)
+	}
+
+	#[inline]
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
+}
+
+/// Writer wrapper which complements every byte before writing it to the inner writer.
+///
+/// The inner writer is held as a trait object, so that nested reversed values do not produce an
+/// ever growing chain of writer types.
+struct InvertingWriter<'a> {
+	inner: &'a mut dyn Write,
+}
+
+impl Write for InvertingWriter<'_> {
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+		let mut inverted = [0; 256];
+		let len = buf.len().min(inverted.len());
+		for (to, from) in inverted.iter_mut().zip(buf) {
+			*to = !from;
+		}
+		self.inner.write(&inverted[..len])
+	}
+
+	#[inline]
+	fn flush(&mut self) -> io::Result<()> {
+		self.inner.flush()
+	}
 }
 
 /// Errors that might occur while serializing.
@@ -95,6 +192,29 @@ pub enum Error {
 	Io(#[from] io::Error),
 }
 
+/// Errors compare equal if they are the same variant with equal contents, except that I/O errors
+/// are compared by their [`io::ErrorKind`] only.
+impl PartialEq for Error {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Error::Message(a), Error::Message(b)) => a == b,
+			(Error::Io(a), Error::Io(b)) => a.kind() == b.kind(),
+			_ => false,
+		}
+	}
+}
+
+/// Errors are converted into I/O errors by unwrapping I/O errors, and otherwise as
+/// [`io::ErrorKind::InvalidInput`], since the value being serialized was not supported.
+impl From<Error> for io::Error {
+	fn from(err: Error) -> io::Error {
+		match err {
+			Error::Io(err) => err,
+			err => io::Error::new(io::ErrorKind::InvalidInput, err),
+		}
+	}
+}
+
 impl serde::ser::Error for Error {
 	fn custom<T: fmt::Display>(msg: T) -> Self {
 		Error::Message(msg.to_string())
@@ -114,7 +234,24 @@ where
 	Ok(bytes)
 }
 
-/// Serialize data into the given vector of `u8` bytes.
+/// Serialize data into a vector of `u8` bytes, which is allocated up front with the given
+/// capacity.
+///
+/// Passing the result of [`serialized_size`] as the capacity serializes the value with a single
+/// allocation.
+pub fn serialize_with_capacity<T>(capacity: usize, v: &T) -> Result<Vec<u8>>
+where
+	T: Serialize + ?Sized,
+{
+	let mut bytes = Vec::with_capacity(capacity);
+	serialize_into(&mut bytes, v)?;
+	Ok(bytes)
+}
+
+/// Serialize data into the given writer.
+///
+/// The writer is not flushed, so callers writing into a buffered writer such as a `BufWriter`
+/// must flush it afterwards.
 pub fn serialize_into<W, T>(writer: W, value: &T) -> Result<()>
 where
 	W: Write,
@@ -124,6 +261,108 @@ where
 	value.serialize(&mut serializer)
 }
 
+/// Serialize the items of an iterator into the given writer, as a sequence.
+///
+/// The output is identical to serializing a `Vec` of the same items, without collecting them
+/// first.
+pub fn serialize_iter_into<W, I>(writer: W, iter: I) -> Result<()>
+where
+	W: Write,
+	I: IntoIterator,
+	I::Item: Serialize,
+{
+	let mut serializer = Serializer::new(writer);
+	serde::Serializer::collect_seq(&mut serializer, iter)
+}
+
+/// Returns the number of bytes `value` serializes into, without allocating a buffer for them.
+pub fn serialized_size<T>(value: &T) -> Result<usize>
+where
+	T: Serialize + ?Sized,
+{
+	let mut serializer = Serializer::new(io::sink());
+	value.serialize(&mut serializer)?;
+	Ok(serializer.bytes_written())
+}
+
+/// Serialize data into the given slice of `u8` bytes, returning the number of bytes written.
+///
+/// An error is returned if the serialized value does not fit into the slice.
+pub fn serialize_into_slice<T>(buf: &mut [u8], value: &T) -> Result<usize>
+where
+	T: Serialize + ?Sized,
+{
+	let mut serializer = Serializer::new(buf);
+	value.serialize(&mut serializer)?;
+	Ok(serializer.bytes_written())
+}
+
+/// Compare two serialized values.
+///
+/// Values serialized by this crate sort in the same order as the original values, so this is a
+/// plain lexicographic comparison of the bytes. It exists to make that intent explicit at call
+/// sites which compare keys without deserializing them. Both values must have been serialized
+/// from the same type.
+pub fn cmp_encoded(a: &[u8], b: &[u8]) -> Ordering {
+	a.cmp(b)
+}
+
+/// Returns the smallest byte string which sorts after every byte string starting with `prefix`.
+///
+/// Serialized structs and tuples are the concatenation of their serialized fields, so the keys
+/// whose leading fields equal some prefix of fields are exactly the byte strings which start with
+/// that prefix serialized as a tuple. Together with this function, that gives the bounds of a
+/// range scan:
+///
+/// ```
+/// let prefix = storekey::serialize(&("users", 7u32)).unwrap();
+/// let end = storekey::successor(&prefix).unwrap();
+///
+/// let key = storekey::serialize(&("users", 7u32, "name")).unwrap();
+/// assert!(prefix <= key && key < end);
+/// let next = storekey::serialize(&("users", 8u32, "name")).unwrap();
+/// assert!(next >= end);
+/// ```
+///
+/// The last byte which is not `0xff` is incremented and any bytes after it are dropped. Returns
+/// `None` if every byte is `0xff`, including for an empty prefix, since no byte string sorts after
+/// all of the strings with such a prefix.
+pub fn successor(prefix: &[u8]) -> Option<Vec<u8>> {
+	let last = prefix.iter().rposition(|b| *b != 0xff)?;
+	let mut end = prefix[..=last].to_vec();
+	end[last] += 1;
+	Some(end)
+}
+
+/// Formats serialized bytes for debugging, without knowing the type they were serialized from.
+///
+/// Each byte is printed in hex, separated by spaces. A run of printable ASCII characters directly
+/// followed by a NUL byte is likely to be a string and its terminator, so it is printed quoted
+/// instead, followed by the `00` terminator. Other bytes are never interpreted, so terminators
+/// and integer bytes are shown as plain hex.
+///
+/// ```
+/// let bytes = storekey::serialize(&("ab", 258u16)).unwrap();
+/// assert_eq!(storekey::debug_dump(&bytes), r#""ab" 00 01 02"#);
+/// ```
+pub fn debug_dump(bytes: &[u8]) -> String {
+	let mut out = Vec::new();
+	let mut rest = bytes;
+	while !rest.is_empty() {
+		let printable = rest.iter().take_while(|b| b.is_ascii_graphic() || **b == b' ').count();
+		if printable > 0 && rest.get(printable) == Some(&0) {
+			let text = std::str::from_utf8(&rest[..printable]).expect("printable ASCII is UTF8");
+			out.push(format!("{text:?}"));
+			out.push("00".to_owned());
+			rest = &rest[printable + 1..];
+		} else {
+			out.push(format!("{:02x}", rest[0]));
+			rest = &rest[1..];
+		}
+	}
+	out.join(" ")
+}
+
 impl<W> Serializer<W>
 where
 	W: Write,
@@ -131,10 +370,40 @@ where
 	/// Creates a new ordered bytes encoder whose output will be written to the provided writer.
 	pub fn new(writer: W) -> Serializer<W> {
 		Serializer {
-			writer,
+			writer: CountingWriter {
+				inner: writer,
+				count: 0,
+			},
+			canonical_nan: false,
+			tagged: false,
 		}
 	}
 
+	/// Makes this serializer replace every NaN float with a single canonical NaN before encoding.
+	///
+	/// By default the bits of each NaN are encoded as they are, so NaNs with different signs or
+	/// payloads encode to different bytes and sort at either end of the other values. With this
+	/// option every NaN encodes identically to `f32::NAN` or `f64::NAN`, sorting after positive
+	/// infinity.
+	pub fn canonical_nan(mut self) -> Self {
+		self.canonical_nan = true;
+		self
+	}
+
+	/// Returns the total number of bytes written by this serializer so far.
+	pub fn bytes_written(&self) -> usize {
+		self.writer.count
+	}
+
+	/// Flushes the underlying writer.
+	///
+	/// The serializer never flushes on its own, so when writing into a buffered writer the last
+	/// bytes of output may remain buffered until this is called.
+	pub fn flush(&mut self) -> Result<()> {
+		self.writer.flush()?;
+		Ok(())
+	}
+
 	/// Encode a `u64` into a variable number of bytes.
 	///
 	/// The variable-length encoding scheme uses between 1 and 9 bytes depending on the value.
@@ -327,7 +596,7 @@ where
 	}
 }
 
-impl<'a, W> serde::Serializer for &'a mut Serializer<W>
+impl<W> serde::Serializer for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -370,6 +639,11 @@ where
 		Ok(())
 	}
 
+	fn serialize_i128(self, v: i128) -> Result<()> {
+		self.writer.write_i128::<BE>(v ^ i128::MIN)?;
+		Ok(())
+	}
+
 	fn serialize_u8(self, v: u8) -> Result<()> {
 		self.writer.write_u8(v)?;
 		Ok(())
@@ -390,7 +664,17 @@ where
 		Ok(())
 	}
 
+	fn serialize_u128(self, v: u128) -> Result<()> {
+		self.writer.write_u128::<BE>(v)?;
+		Ok(())
+	}
+
 	fn serialize_f32(self, v: f32) -> Result<()> {
+		let v = if self.canonical_nan && v.is_nan() {
+			f32::NAN
+		} else {
+			v
+		};
 		let val = v.to_bits() as i32;
 		let t = (val >> 31) | i32::MIN;
 		self.writer.write_i32::<BE>(val ^ t)?;
@@ -398,6 +682,11 @@ where
 	}
 
 	fn serialize_f64(self, v: f64) -> Result<()> {
+		let v = if self.canonical_nan && v.is_nan() {
+			f64::NAN
+		} else {
+			v
+		};
 		let val = v.to_bits() as i64;
 		let t = (val >> 63) | i64::MIN;
 		self.writer.write_i64::<BE>(val ^ t)?;
@@ -454,10 +743,24 @@ where
 		self.serialize_u32(variant_index)
 	}
 
-	fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+	fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
 	where
 		T: ?Sized + Serialize,
 	{
+		if name == reverse::TOKEN {
+			let mut inverted = Serializer::new(InvertingWriter {
+				inner: &mut self.writer,
+			});
+			inverted.canonical_nan = self.canonical_nan;
+			inverted.tagged = self.tagged;
+			return value.serialize(&mut inverted);
+		}
+		if name == tagged::TOKEN {
+			let tagged = std::mem::replace(&mut self.tagged, true);
+			let result = value.serialize(&mut *self);
+			self.tagged = tagged;
+			return result;
+		}
 		value.serialize(self)
 	}
 
@@ -524,7 +827,7 @@ where
 
 // Compound Implementations.
 
-impl<'a, W> serde::ser::SerializeSeq for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeSeq for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -535,6 +838,9 @@ where
 	where
 		T: ?Sized + Serialize,
 	{
+		if self.tagged {
+			self.writer.write_u8(0x02)?;
+		}
 		value.serialize(&mut **self)
 	}
 
@@ -544,7 +850,7 @@ where
 	}
 }
 
-impl<'a, W> serde::ser::SerializeTuple for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeTuple for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -563,7 +869,7 @@ where
 	}
 }
 
-impl<'a, W> serde::ser::SerializeTupleStruct for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeTupleStruct for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -582,7 +888,7 @@ where
 	}
 }
 
-impl<'a, W> serde::ser::SerializeTupleVariant for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeTupleVariant for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -601,7 +907,7 @@ where
 	}
 }
 
-impl<'a, W> serde::ser::SerializeMap for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeMap for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -612,6 +918,9 @@ where
 	where
 		T: ?Sized + Serialize,
 	{
+		if self.tagged {
+			self.writer.write_u8(0x02)?;
+		}
 		key.serialize(&mut **self)
 	}
 
@@ -628,7 +937,7 @@ where
 	}
 }
 
-impl<'a, W> serde::ser::SerializeStruct for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeStruct for &mut Serializer<W>
 where
 	W: Write,
 {
@@ -647,7 +956,7 @@ where
 	}
 }
 
-impl<'a, W> serde::ser::SerializeStructVariant for &'a mut Serializer<W>
+impl<W> serde::ser::SerializeStructVariant for &mut Serializer<W>
 where
 	W: Write,
 {
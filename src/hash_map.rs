@@ -0,0 +1,74 @@
+//! Strict deserialization of [`HashMap`] values, for use with `#[serde(with)]`.
+//!
+//! serde's own `HashMap` implementation silently keeps the last value when a key appears more
+//! than once. This module instead rejects any input containing a duplicate key, which catches
+//! corrupt input. Serialization is unchanged, so entries are written in the map's iteration order
+//! and equal maps may serialize differently; use a `BTreeMap` with the
+//! [`sorted_map`](crate::sorted_map) module where the encoding must be canonical.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use std::collections::HashMap;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Index {
+//!     #[serde(with = "storekey::hash_map")]
+//!     entries: HashMap<u32, String>,
+//! }
+//! ```
+
+use serde::de::{Deserialize, Deserializer, Error as _, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::marker::PhantomData;
+
+/// Serialize a `HashMap` exactly as serde would.
+pub fn serialize<K, V, H, S>(map: &HashMap<K, V, H>, serializer: S) -> Result<S::Ok, S::Error>
+where
+	K: Serialize,
+	V: Serialize,
+	S: Serializer,
+{
+	map.serialize(serializer)
+}
+
+/// Deserialize a `HashMap`, failing if any key appears more than once.
+pub fn deserialize<'de, K, V, H, D>(deserializer: D) -> Result<HashMap<K, V, H>, D::Error>
+where
+	K: Deserialize<'de> + Eq + Hash,
+	V: Deserialize<'de>,
+	H: BuildHasher + Default,
+	D: Deserializer<'de>,
+{
+	struct HashMapVisitor<K, V, H>(PhantomData<(K, V, H)>);
+
+	impl<'de, K, V, H> Visitor<'de> for HashMapVisitor<K, V, H>
+	where
+		K: Deserialize<'de> + Eq + Hash,
+		V: Deserialize<'de>,
+		H: BuildHasher + Default,
+	{
+		type Value = HashMap<K, V, H>;
+
+		fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+			formatter.write_str("a map without duplicate keys")
+		}
+
+		fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+		where
+			A: MapAccess<'de>,
+		{
+			let mut map = HashMap::with_hasher(H::default());
+			while let Some((key, value)) = access.next_entry::<K, V>()? {
+				if map.insert(key, value).is_some() {
+					return Err(A::Error::custom("duplicate map key"));
+				}
+			}
+			Ok(map)
+		}
+	}
+
+	deserializer.deserialize_map(HashMapVisitor(PhantomData))
+}
@@ -10,17 +10,19 @@
 //! #### Supported Data Types
 //!
 //! `storekey` currently supports all Rust primitives, strings, options, structs, enums, vecs, and
-//! tuples. See **Serializer** for details on the serialization format.
+//! tuples. See **Serializer** for details on the serialization format. Values can be wrapped in
+//! **Reverse** to serialize them in descending order.
 //!
 //! #### Type Evolution
 //!
 //! In general, the exact type of a serialized value must be known in order to correctly
 //! deserialize it. For structs and enums, the type is effectively frozen once any values of the
 //! type have been serialized: changes to the struct or enum will cause deserialization of already
-//! serialized values to fail or return incorrect values. The only exception is adding new variants
+//! serialized values to fail or return incorrect values. One exception is adding new variants
 //! to the end of an existing enum. Enum variants may *not* change type, be removed, or be
 //! reordered. All changes to structs, including adding, removing, reordering, or changing the type
-//! of a field are forbidden.
+//! of a field are forbidden, except that `Option` fields may be appended to the end of a
+//! top-level struct using the **trailing** module.
 //!
 //! These restrictions lead to a few best-practices when using `storekey` serialization:
 //!
@@ -35,8 +37,29 @@
 //!   your enum has less than 16 variants, then the overhead is just a single byte in serialized
 //!   output.
 
+pub mod binary_heap;
+pub mod bytes;
+pub mod checksum;
 pub mod decode;
 pub mod encode;
+pub mod hash_map;
+pub mod monotonic;
+pub mod reverse;
+pub mod sorted_map;
+pub mod system_time;
+pub mod tagged;
+#[cfg(feature = "test-util")]
+pub mod test_util;
+pub mod trailing;
 
-pub use self::decode::{deserialize, deserialize_from, Deserializer};
-pub use self::encode::{serialize, serialize_into, Serializer};
+pub use self::bytes::{ByteSlice, ByteString};
+pub use self::decode::{
+	deserialize, deserialize_from, deserialize_from_with_offset, deserialize_iter,
+	deserialize_lossy, deserialize_with_offset, Deserializer,
+};
+pub use self::encode::{
+	cmp_encoded, debug_dump, serialize, serialize_into, serialize_into_slice, serialize_iter_into,
+	serialize_with_capacity, serialized_size, successor, Serializer,
+};
+pub use self::reverse::Reverse;
+pub use self::tagged::Tagged;
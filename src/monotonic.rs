@@ -0,0 +1,48 @@
+//! Order-preserving serialization of monotonic time.
+//!
+//! [`Instant`] is opaque and only meaningful within the process which created it, so it can not be
+//! serialized. Times which need to be persisted should be stored as a [`SystemTime`] using the
+//! [`system_time`](crate::system_time) module. Times which only need to sort correctly relative to
+//! a known starting instant, such as keys in a process-local cache, can instead be stored as a
+//! [`MonotonicNanos`] offset from that instant.
+//!
+//! ```
+//! use std::time::{Duration, Instant};
+//! use storekey::monotonic::MonotonicNanos;
+//!
+//! let base = Instant::now();
+//! let later = base + Duration::from_millis(5);
+//!
+//! let key = storekey::serialize(&MonotonicNanos::since(base, later)).unwrap();
+//! let nanos: MonotonicNanos = storekey::deserialize(&key).unwrap();
+//! assert_eq!(nanos.to_instant(base), Some(later));
+//! ```
+//!
+//! [`SystemTime`]: std::time::SystemTime
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// A number of nanoseconds elapsed since some base [`Instant`], serialized as a `u64`.
+///
+/// Later instants serialize after earlier ones, as long as they share the same base.
+#[derive(
+	Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+pub struct MonotonicNanos(pub u64);
+
+impl MonotonicNanos {
+	/// Returns the time elapsed from `base` to `instant`.
+	///
+	/// Instants before `base` saturate to zero, and instants more than `u64::MAX` nanoseconds
+	/// (around 584 years) after it saturate to `u64::MAX`.
+	pub fn since(base: Instant, instant: Instant) -> Self {
+		let nanos = instant.saturating_duration_since(base).as_nanos();
+		MonotonicNanos(u64::try_from(nanos).unwrap_or(u64::MAX))
+	}
+
+	/// Returns the instant this many nanoseconds after `base`, if it can be represented.
+	pub fn to_instant(self, base: Instant) -> Option<Instant> {
+		base.checked_add(Duration::from_nanos(self.0))
+	}
+}
@@ -0,0 +1,71 @@
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The newtype struct name used to recognise `Reverse` values inside the storekey serializer and
+/// deserializer.
+pub(crate) const TOKEN: &str = "$storekey::private::Reverse";
+
+/// A wrapper which serializes its value so that it sorts in descending order.
+///
+/// The bytes produced by serializing the inner value are complemented, so for any two values `a`
+/// and `b` of the same type, `a < b` implies `serialize(&Reverse(a)) > serialize(&Reverse(b))`.
+/// This also holds for variable-length values such as strings and sequences, because their
+/// terminators are complemented along with the rest of the output.
+///
+/// Like [`std::cmp::Reverse`], the `Ord` implementation of this type is the reverse of the inner
+/// type's implementation, so the ordering of the wrapper matches the ordering of its encoding.
+///
+/// Values inside a `Reverse` are always copied when deserialized, so types which borrow from the
+/// input, such as `&str`, can not be deserialized from a `Reverse`.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+pub struct Reverse<T>(pub T);
+
+impl<T: PartialOrd> PartialOrd for Reverse<T> {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		other.0.partial_cmp(&self.0)
+	}
+}
+
+impl<T: Ord> Ord for Reverse<T> {
+	fn cmp(&self, other: &Self) -> Ordering {
+		other.0.cmp(&self.0)
+	}
+}
+
+impl<T: Serialize> Serialize for Reverse<T> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_newtype_struct(TOKEN, &self.0)
+	}
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Reverse<T> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		struct ReverseVisitor<T>(PhantomData<T>);
+
+		impl<'de, T: Deserialize<'de>> Visitor<'de> for ReverseVisitor<T> {
+			type Value = Reverse<T>;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a reversed value")
+			}
+
+			fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+			where
+				D: Deserializer<'de>,
+			{
+				T::deserialize(deserializer).map(Reverse)
+			}
+		}
+
+		deserializer.deserialize_newtype_struct(TOKEN, ReverseVisitor(PhantomData))
+	}
+}
@@ -0,0 +1,71 @@
+//! Strict deserialization of [`BTreeMap`] values, for use with `#[serde(with)]`.
+//!
+//! serde's own `BTreeMap` implementation silently keeps the last value when a key appears more
+//! than once. Since a `BTreeMap` is always serialized in ascending key order, this module instead
+//! rejects any input whose keys are not in strictly ascending order, which catches both duplicate
+//! and out of order keys in corrupt input. Serialization is unchanged.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use std::collections::BTreeMap;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Index {
+//!     #[serde(with = "storekey::sorted_map")]
+//!     entries: BTreeMap<u32, String>,
+//! }
+//! ```
+
+use serde::de::{Deserialize, Deserializer, Error as _, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Serialize a `BTreeMap` exactly as serde would.
+pub fn serialize<K, V, S>(map: &BTreeMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+	K: Serialize,
+	V: Serialize,
+	S: Serializer,
+{
+	map.serialize(serializer)
+}
+
+/// Deserialize a `BTreeMap`, failing if its keys are not in strictly ascending order.
+pub fn deserialize<'de, K, V, D>(deserializer: D) -> Result<BTreeMap<K, V>, D::Error>
+where
+	K: Deserialize<'de> + Ord,
+	V: Deserialize<'de>,
+	D: Deserializer<'de>,
+{
+	struct SortedMapVisitor<K, V>(PhantomData<(K, V)>);
+
+	impl<'de, K, V> Visitor<'de> for SortedMapVisitor<K, V>
+	where
+		K: Deserialize<'de> + Ord,
+		V: Deserialize<'de>,
+	{
+		type Value = BTreeMap<K, V>;
+
+		fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+			formatter.write_str("a map with keys in strictly ascending order")
+		}
+
+		fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+		where
+			A: MapAccess<'de>,
+		{
+			let mut map = BTreeMap::new();
+			while let Some((key, value)) = access.next_entry::<K, V>()? {
+				if map.last_key_value().is_some_and(|(last, _)| *last >= key) {
+					return Err(A::Error::custom("map keys are not in strictly ascending order"));
+				}
+				map.insert(key, value);
+			}
+			Ok(map)
+		}
+	}
+
+	deserializer.deserialize_map(SortedMapVisitor(PhantomData))
+}
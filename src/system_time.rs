@@ -0,0 +1,67 @@
+//! Order-preserving serialization of [`SystemTime`] values, for use with `#[serde(with)]`.
+//!
+//! serde's own `SystemTime` implementation refuses to serialize times before the
+//! [`UNIX_EPOCH`]. This module instead serializes the signed offset from the epoch as an `i64`
+//! number of seconds, rounded towards negative infinity, followed by a `u32` number of
+//! nanoseconds. Earlier times therefore always sort before later times, including times before
+//! 1970.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use std::time::SystemTime;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(with = "storekey::system_time")]
+//!     at: SystemTime,
+//! }
+//! ```
+
+use serde::de::{Deserialize, Deserializer, Error as _};
+use serde::ser::{Error as _, Serialize, Serializer};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const NANOS_PER_SEC: u32 = 1_000_000_000;
+
+/// Serialize a `SystemTime` as its signed offset from the `UNIX_EPOCH`.
+pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+	S: Serializer,
+{
+	let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+		Ok(after) => {
+			let secs = i64::try_from(after.as_secs())
+				.map_err(|_| S::Error::custom("SystemTime is too far after UNIX_EPOCH"))?;
+			(secs, after.subsec_nanos())
+		}
+		Err(err) => {
+			let before = err.duration();
+			let secs = i64::try_from(before.as_secs())
+				.ok()
+				.and_then(|s| s.checked_neg())
+				.and_then(|s| s.checked_sub((before.subsec_nanos() > 0) as i64))
+				.ok_or_else(|| S::Error::custom("SystemTime is too far before UNIX_EPOCH"))?;
+			(secs, (NANOS_PER_SEC - before.subsec_nanos()) % NANOS_PER_SEC)
+		}
+	};
+	(secs, nanos).serialize(serializer)
+}
+
+/// Deserialize a `SystemTime` serialized by [`serialize`].
+pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+where
+	D: Deserializer<'de>,
+{
+	let (secs, nanos) = <(i64, u32)>::deserialize(deserializer)?;
+	if nanos >= NANOS_PER_SEC {
+		return Err(D::Error::custom("SystemTime nanoseconds out of range"));
+	}
+	let time = if secs >= 0 {
+		UNIX_EPOCH.checked_add(Duration::new(secs as u64, nanos))
+	} else {
+		UNIX_EPOCH
+			.checked_sub(Duration::from_secs(secs.unsigned_abs()))
+			.and_then(|t| t.checked_add(Duration::from_nanos(nanos as u64)))
+	};
+	time.ok_or_else(|| D::Error::custom("SystemTime out of range"))
+}
@@ -0,0 +1,67 @@
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The newtype struct name used to recognise `Tagged` values inside the storekey serializer and
+/// deserializer.
+pub(crate) const TOKEN: &str = "$storekey::private::Tagged";
+
+/// A wrapper which serializes every sequence and map inside its value with a tag before each
+/// entry.
+///
+/// By default the entries of a sequence or map are written one after another, followed by a
+/// `0x01` terminator. An entry whose encoding itself starts with `0x01`, such as an empty inner
+/// `Vec`, `Some(_)` or the `u8` value `1`, is then read back as the end of the sequence. Inside a
+/// `Tagged` value each entry is preceded by a `0x02` tag instead, so nested sequences decode
+/// unambiguously, and a sequence sorts before any longer sequence which it is a prefix of.
+///
+/// The tags apply to every sequence and map nested anywhere inside the value, so values must be
+/// deserialized as the same `Tagged` type which they were serialized as. Other serde formats see
+/// a plain newtype.
+///
+/// ```
+/// use storekey::{deserialize, serialize, Tagged};
+///
+/// let value = Tagged(vec![vec![], vec![1u8]]);
+/// let bytes = serialize(&value).unwrap();
+/// assert_eq!(bytes, [2, 1, 2, 2, 1, 1, 1]);
+/// assert_eq!(deserialize::<Tagged<Vec<Vec<u8>>>>(&bytes).unwrap(), value);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Tagged<T>(pub T);
+
+impl<T: Serialize> Serialize for Tagged<T> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_newtype_struct(TOKEN, &self.0)
+	}
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Tagged<T> {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		struct TaggedVisitor<T>(PhantomData<T>);
+
+		impl<'de, T: Deserialize<'de>> Visitor<'de> for TaggedVisitor<T> {
+			type Value = Tagged<T>;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a tagged value")
+			}
+
+			fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+			where
+				D: Deserializer<'de>,
+			{
+				T::deserialize(deserializer).map(Tagged)
+			}
+		}
+
+		deserializer.deserialize_newtype_struct(TOKEN, TaggedVisitor(PhantomData))
+	}
+}
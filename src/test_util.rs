@@ -0,0 +1,44 @@
+//! Helpers for testing `Serialize` and `Deserialize` implementations against this format.
+//!
+//! These are only available with the `test-util` feature enabled.
+
+use crate::{cmp_encoded, serialize, Deserializer};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::Debug;
+
+/// Assert that `value` deserializes back into an equal value after being serialized.
+///
+/// Panics if serialization or deserialization fails, if the result differs from `value`, or if
+/// deserialization does not consume all of the serialized bytes.
+pub fn assert_roundtrip<T>(value: &T)
+where
+	T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+	let encoded = serialize(value).expect("failed to serialize value");
+	let mut deserializer = Deserializer::from_slice(&encoded);
+	let decoded = T::deserialize(&mut deserializer)
+		.unwrap_or_else(|err| panic!("failed to deserialize {value:?} from {encoded:?}: {err}"));
+	assert_eq!(&decoded, value, "{value:?} did not roundtrip through {encoded:?}");
+	let trailing = deserializer.peek_u8().expect("failed to read trailing bytes");
+	assert!(trailing.is_none(), "{value:?} did not consume all of {encoded:?}");
+}
+
+/// Assert that serializing `a` and `b` preserves their relative order.
+///
+/// Panics if the values compare differently once serialized. Values which are not comparable,
+/// such as NaN floats, are only checked to serialize successfully.
+pub fn assert_order_preserving<T>(a: &T, b: &T)
+where
+	T: Serialize + PartialOrd + Debug + ?Sized,
+{
+	let encoded_a = serialize(a).expect("failed to serialize value");
+	let encoded_b = serialize(b).expect("failed to serialize value");
+	if let Some(ordering) = a.partial_cmp(b) {
+		assert_eq!(
+			cmp_encoded(&encoded_a, &encoded_b),
+			ordering,
+			"order of {a:?} and {b:?} not preserved: {encoded_a:?} and {encoded_b:?}",
+		);
+	}
+}
@@ -0,0 +1,78 @@
+//! Forward compatible trailing `Option` fields, for use with `#[serde(with)]`.
+//!
+//! Struct layouts are normally frozen once values have been serialized, because fields are
+//! serialized one after another without any framing. This module allows one exception: an
+//! `Option` field appended to the end of a struct deserializes as `None` if the input ends before
+//! it, so values serialized before the field was added can still be deserialized. Otherwise the
+//! field is serialized and deserialized exactly like a plain `Option`.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct OldKey {
+//!     id: u64,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct NewKey {
+//!     id: u64,
+//!     #[serde(with = "storekey::trailing")]
+//!     shard: Option<u16>,
+//! }
+//!
+//! let old = storekey::serialize(&OldKey { id: 7 }).unwrap();
+//! let new: NewKey = storekey::deserialize(&old).unwrap();
+//! assert_eq!((new.id, new.shard), (7, None));
+//! ```
+//!
+//! Only the end of the input is detected, so this only works for fields at the very end of the
+//! serialized value: the last fields of a top-level struct, and not of a struct nested inside
+//! another value.
+
+use serde::de::{Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use std::fmt;
+use std::marker::PhantomData;
+
+/// The newtype struct name used to recognise trailing fields inside the storekey deserializer.
+pub(crate) const TOKEN: &str = "$storekey::private::Trailing";
+
+/// Serialize a trailing `Option` field exactly like a plain `Option`.
+pub fn serialize<T, S>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+	T: Serialize,
+	S: Serializer,
+{
+	serializer.serialize_newtype_struct(TOKEN, value)
+}
+
+/// Deserialize a trailing `Option` field, returning `None` at the end of the input.
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+	T: Deserialize<'de>,
+	D: Deserializer<'de>,
+{
+	struct TrailingVisitor<T>(PhantomData<T>);
+
+	impl<'de, T: Deserialize<'de>> Visitor<'de> for TrailingVisitor<T> {
+		type Value = Option<T>;
+
+		fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+			formatter.write_str("an optional trailing value")
+		}
+
+		fn visit_none<E>(self) -> Result<Self::Value, E> {
+			Ok(None)
+		}
+
+		fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+		where
+			D: Deserializer<'de>,
+		{
+			Option::deserialize(deserializer)
+		}
+	}
+
+	deserializer.deserialize_newtype_struct(TOKEN, TrailingVisitor(PhantomData))
+}
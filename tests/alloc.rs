@@ -0,0 +1,114 @@
+use serde::de::{Deserialize, Deserializer, SeqAccess, Visitor};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::fmt;
+use storekey::{deserialize, serialize};
+
+/// Counts the allocations made by each thread, so tests can check that a code path does not
+/// allocate while other tests run in parallel.
+struct CountingAllocator;
+
+thread_local! {
+	static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn allocations() -> usize {
+	ALLOCATIONS.with(Cell::get)
+}
+
+fn count_allocation() {
+	let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+	unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+		count_allocation();
+		System.alloc(layout)
+	}
+
+	unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+		System.dealloc(ptr, layout)
+	}
+
+	unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+		count_allocation();
+		System.realloc(ptr, layout, new_size)
+	}
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The length of a string, read without allocating.
+struct Len(usize);
+
+impl<'de> Deserialize<'de> for Len {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct LenVisitor;
+
+		impl<'de> Visitor<'de> for LenVisitor {
+			type Value = Len;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a string")
+			}
+
+			fn visit_str<E>(self, v: &str) -> Result<Len, E> {
+				Ok(Len(v.len()))
+			}
+		}
+
+		deserializer.deserialize_str(LenVisitor)
+	}
+}
+
+/// The total length of a sequence of strings, read without allocating.
+struct TotalLen(usize);
+
+impl<'de> Deserialize<'de> for TotalLen {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct TotalLenVisitor;
+
+		impl<'de> Visitor<'de> for TotalLenVisitor {
+			type Value = TotalLen;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a sequence of strings")
+			}
+
+			fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<TotalLen, A::Error> {
+				let mut total = 0;
+				while let Some(Len(len)) = seq.next_element()? {
+					total += len;
+				}
+				Ok(TotalLen(total))
+			}
+		}
+
+		deserializer.deserialize_seq(TotalLenVisitor)
+	}
+}
+
+#[test]
+fn reader_scratch_buffer() {
+	let strings: Vec<String> = (0..100).map(|i| "x".repeat(150 + i)).collect();
+	let bytes = serialize(&strings).unwrap();
+	let total = strings.iter().map(String::len).sum();
+
+	// Every string is copied through the same scratch buffer, so once it has grown to fit the
+	// longest string, reading the same strings again allocates nothing.
+	let twice = [&bytes[..], &bytes[..]].concat();
+	let mut deserializer = storekey::Deserializer::from_reader(&twice[..]);
+	let TotalLen(len) = TotalLen::deserialize(&mut deserializer).unwrap();
+	assert_eq!(len, total);
+	let before = allocations();
+	let TotalLen(len) = TotalLen::deserialize(&mut deserializer).unwrap();
+	assert_eq!(allocations() - before, 0);
+	assert_eq!(len, total);
+
+	// Deserializing from a slice borrows every string, so nothing is allocated at all.
+	let before = allocations();
+	let TotalLen(len) = deserialize(&bytes).unwrap();
+	assert_eq!(allocations() - before, 0);
+	assert_eq!(len, total);
+}
@@ -1,6 +1,6 @@
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::fmt::Debug;
-use storekey::{deserialize, serialize};
+use storekey::{deserialize, deserialize_from, serialize, Tagged};
 
 macro_rules! roundtrip_inner {
 	($v: expr) => {
@@ -29,6 +29,7 @@ fn expect<T: Serialize + DeserializeOwned + PartialEq + Debug>(t: T, expected: &
 	assert_eq!(deserialize::<T>(expected).unwrap(), t);
 }
 
+#[allow(clippy::eq_op)]
 fn less<T: Serialize + Debug + PartialEq + PartialOrd>(a: T, b: T) {
 	// Exception for float NaNs.
 	if a == a && b == b {
@@ -55,6 +56,17 @@ fn boolean() {
 fn option() {
 	expect(None::<u8>, &[0]);
 	expect(Some::<u8>(5), &[1, 5]);
+	expect(Some(None::<u8>), &[1, 0]);
+	expect(Some(Some(0u8)), &[1, 1, 0]);
+	expect(Tagged(vec![None, Some(2u8)]), &[2, 0, 2, 1, 2, 1]);
+	less(None, Some(0u8));
+	less(Some(None), Some(Some(0u8)));
+
+	// Only `0` and `1` are valid tags, so that a format change can not be decoded silently.
+	for tag in [2, 3, 0xff] {
+		let err = deserialize::<Option<u8>>(&[tag, 5]).unwrap_err();
+		assert!(err.to_string().contains("for option tag"), "{err}");
+	}
 }
 
 #[test]
@@ -68,6 +80,26 @@ fn int() {
 	less(-1, 0);
 	less(-1, 1);
 	less(i64::MIN, i64::MAX);
+
+	for pair in
+		[u128::MIN, 1, u64::MAX as u128, u64::MAX as u128 + 1, u128::MAX - 1, u128::MAX].windows(2)
+	{
+		less(pair[0], pair[1]);
+	}
+	for pair in
+		[i128::MIN, i128::MIN + 1, i64::MIN as i128 - 1, -1, 0, 1, i64::MAX as i128 + 1, i128::MAX]
+			.windows(2)
+	{
+		less(pair[0], pair[1]);
+	}
+	expect(i128::MIN, &[0; 16]);
+	expect(-1i128, &[&[0x7f][..], &[0xff; 15]].concat());
+	expect(u128::MAX, &[0xff; 16]);
+
+	// Signed and unsigned integers are not comparable with each other.
+	assert_eq!(serialize(&5i32).unwrap(), [0x80, 0, 0, 5]);
+	assert_eq!(serialize(&5u32).unwrap(), [0, 0, 0, 5]);
+	assert!(serialize(&-1i32).unwrap() > serialize(&5u32).unwrap());
 }
 
 #[test]
@@ -128,6 +160,18 @@ fn chars() {
 	expect('a', &[b'a', 0]);
 
 	assert!(serialize(&'\0').is_err());
+
+	less('\u{7f}', '\u{80}');
+	less('\u{d7ff}', '\u{e000}');
+	less('\u{ffff}', '\u{10000}');
+	expect(char::MAX, &[0xf4, 0x8f, 0xbf, 0xbf, 0]);
+
+	// Encoded surrogates and code points above `char::MAX` are not valid UTF8.
+	for bytes in [&[0xed, 0xa0, 0x80, 0][..], &[0xf4, 0x90, 0x80, 0x80, 0]] {
+		let err = deserialize::<char>(bytes).unwrap_err();
+		assert!(err.to_string().starts_with("Attempted to deserialize invalid UTF8"));
+	}
+	assert!(deserialize::<char>(&[0]).is_err());
 }
 
 #[test]
@@ -250,3 +294,1379 @@ fn structs() {
 
 	roundtrip!(lq);
 }
+
+#[test]
+fn ip_addresses() {
+	use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+	expect(Ipv4Addr::new(0, 0, 0, 0), &[0, 0, 0, 0]);
+	expect(Ipv4Addr::new(255, 255, 255, 255), &[255, 255, 255, 255]);
+	expect(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), &[0, 0, 0, 0, 10, 0, 0, 1]);
+
+	let mapped = Ipv4Addr::new(192, 168, 1, 1).to_ipv6_mapped();
+	roundtrip!(mapped);
+	roundtrip!(IpAddr::V6(mapped));
+	roundtrip!(Ipv6Addr::UNSPECIFIED);
+	roundtrip!(IpAddr::V6(Ipv6Addr::new(
+		0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff
+	)));
+
+	less(Ipv4Addr::new(0, 0, 0, 0), Ipv4Addr::new(0, 0, 0, 1));
+	less(Ipv4Addr::new(9, 255, 255, 255), Ipv4Addr::new(10, 0, 0, 0));
+	less(Ipv4Addr::new(10, 0, 0, 0), Ipv4Addr::new(255, 255, 255, 255));
+	less(Ipv6Addr::UNSPECIFIED, Ipv6Addr::LOCALHOST);
+	less(Ipv6Addr::LOCALHOST, mapped);
+	less(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 255)), IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+}
+
+#[test]
+fn socket_addresses() {
+	use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+	let v4 = |a, port| SocketAddrV4::new(Ipv4Addr::new(10, 0, 0, a), port);
+	expect(v4(1, 258), &[10, 0, 0, 1, 1, 2]);
+	expect(SocketAddr::V4(v4(1, 80)), &[0, 0, 0, 0, 10, 0, 0, 1, 0, 80]);
+	roundtrip!(v4(1, u16::MAX));
+	roundtrip!(SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 0, 0)));
+
+	less(v4(1, u16::MAX), v4(2, 0));
+	less(v4(1, 80), v4(1, 443));
+	less(
+		SocketAddr::V4(v4(255, u16::MAX)),
+		SocketAddr::V6(SocketAddrV6::new(Ipv6Addr::UNSPECIFIED, 0, 0, 0)),
+	);
+
+	// Flow info and scope IDs are not part of the encoding.
+	let scoped = SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 7, 3);
+	let bytes = serialize(&scoped).unwrap();
+	assert_eq!(bytes, serialize(&SocketAddrV6::new(Ipv6Addr::LOCALHOST, 443, 0, 0)).unwrap());
+	let decoded: SocketAddrV6 = deserialize(&bytes).unwrap();
+	assert_eq!((decoded.flowinfo(), decoded.scope_id()), (0, 0));
+}
+
+#[test]
+fn non_zero() {
+	use std::num::{
+		NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroU128, NonZeroU16,
+		NonZeroU32, NonZeroU64, NonZeroU8,
+	};
+
+	expect(NonZeroU32::MAX, &[255, 255, 255, 255]);
+	expect(NonZeroU8::new(5).unwrap(), &[5]);
+	expect(NonZeroI8::new(-1).unwrap(), &[0x7f]);
+	roundtrip!(NonZeroU16::new(1).unwrap());
+	roundtrip!(NonZeroU64::MAX);
+	roundtrip!(NonZeroU128::MAX);
+	roundtrip!(NonZeroI16::MIN);
+	roundtrip!(NonZeroI32::MAX);
+	roundtrip!(NonZeroI64::MIN);
+	roundtrip!(NonZeroI128::MIN);
+
+	less(NonZeroU64::new(1).unwrap(), NonZeroU64::MAX);
+	less(NonZeroI32::MIN, NonZeroI32::new(-1).unwrap());
+	less(NonZeroI32::new(-1).unwrap(), NonZeroI32::new(1).unwrap());
+
+	assert!(deserialize::<NonZeroU32>(&[0, 0, 0, 0]).is_err());
+	assert!(deserialize::<NonZeroI64>(&serialize(&0i64).unwrap()).is_err());
+}
+
+#[test]
+fn vec_deque() {
+	use std::collections::VecDeque;
+
+	let vec = vec![1000u32, 2000, 3000];
+	let mut deque: VecDeque<u32> = VecDeque::new();
+	deque.push_back(2000);
+	deque.push_back(3000);
+	deque.push_front(1000);
+
+	assert_eq!(serialize(&deque).unwrap(), serialize(&vec).unwrap());
+	assert_eq!(deserialize::<VecDeque<u32>>(&serialize(&vec).unwrap()).unwrap(), deque);
+	roundtrip!(deque);
+	roundtrip!(VecDeque::<String>::new());
+}
+
+#[test]
+fn sets() {
+	use std::collections::{BTreeSet, HashSet};
+
+	expect(BTreeSet::<u8>::new(), &[1]);
+	expect(HashSet::<u8>::new(), &[1]);
+
+	let set: BTreeSet<Vec<u8>> = [vec![0, 5], vec![5, 0], vec![0, 0]].into_iter().collect();
+	assert_eq!(deserialize::<BTreeSet<Vec<u8>>>(&serialize(&set).unwrap()).unwrap(), set);
+
+	let set: HashSet<Vec<u8>> = [vec![0, 5], vec![5, 0], vec![0, 0]].into_iter().collect();
+	assert_eq!(deserialize::<HashSet<Vec<u8>>>(&serialize(&set).unwrap()).unwrap(), set);
+
+	let set: BTreeSet<String> = ["b", "a", "c"].into_iter().map(String::from).collect();
+	assert_eq!(deserialize::<BTreeSet<String>>(&serialize(&set).unwrap()).unwrap(), set);
+
+	let a: BTreeSet<u32> = [10, 20].into_iter().collect();
+	let b: BTreeSet<u32> = [10, 30].into_iter().collect();
+	less(a, b);
+}
+
+#[test]
+fn reverse() {
+	use storekey::Reverse;
+
+	expect(Reverse(5u8), &[!5]);
+	expect(Reverse("ab".to_owned()), &[!b'a', !b'b', 0xff]);
+
+	roundtrip!(Reverse(42u64));
+	roundtrip!(Reverse("hello".to_owned()));
+	roundtrip!(Reverse(vec!["a".to_owned(), "".to_owned()]));
+	roundtrip!(Reverse(Reverse(7i32)));
+	roundtrip!((Reverse("a".to_owned()), 3u64, Reverse(9u64)));
+	assert_eq!(
+		deserialize_from::<_, (Reverse<String>, Reverse<u64>)>(
+			&serialize(&(Reverse("abc".to_owned()), Reverse(1u64))).unwrap()[..]
+		)
+		.unwrap(),
+		(Reverse("abc".to_owned()), Reverse(1u64)),
+	);
+
+	// Values longer than the input complemented ahead of the deserializer at a time.
+	let long = (
+		Reverse(vec!["x".repeat(300), "y".repeat(100)]),
+		Reverse(serde_bytes::ByteBuf::from(vec![7; 300])),
+		5u8,
+	);
+	roundtrip!(long.clone());
+	let bytes = serialize(&long).unwrap();
+	assert_eq!(deserialize_from::<_, (Reverse<Vec<String>>, _, u8)>(&bytes[..]).unwrap(), long);
+
+	less(Reverse(10u64), Reverse(1u64));
+	less(Reverse("ab".to_owned()), Reverse("a".to_owned()));
+	less(Reverse("b".to_owned()), Reverse("ab".to_owned()));
+	less(Reverse(vec!["a".to_owned(), "b".to_owned()]), Reverse(vec!["a".to_owned()]));
+	less((Reverse("b".to_owned()), Reverse(0u64)), (Reverse("a".to_owned()), Reverse(5u64)));
+	less((Reverse("a".to_owned()), Reverse(5u64)), (Reverse("a".to_owned()), Reverse(4u64)));
+	less((1u8, Reverse("ab".to_owned()), 0u8), (1u8, Reverse("a".to_owned()), 0u8));
+}
+
+#[test]
+fn system_time() {
+	use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+	#[derive(Debug, Clone, PartialEq, PartialOrd, Serialize, Deserialize)]
+	struct Time(#[serde(with = "storekey::system_time")] SystemTime);
+
+	expect(Time(UNIX_EPOCH), &[0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+	expect(Time(UNIX_EPOCH + Duration::new(1, 5)), &[0x80, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 5]);
+	expect(
+		Time(UNIX_EPOCH - Duration::from_nanos(1)),
+		&[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3b, 0x9a, 0xc9, 0xff],
+	);
+
+	let ordering = [
+		UNIX_EPOCH - Duration::new(86_400 * 365 * 100, 0),
+		UNIX_EPOCH - Duration::new(1, 1),
+		UNIX_EPOCH - Duration::new(1, 0),
+		UNIX_EPOCH - Duration::from_nanos(2),
+		UNIX_EPOCH - Duration::from_nanos(1),
+		UNIX_EPOCH,
+		UNIX_EPOCH + Duration::from_nanos(1),
+		UNIX_EPOCH + Duration::new(1, 0),
+		SystemTime::now(),
+	];
+	for window in ordering.windows(2) {
+		less(Time(window[0]), Time(window[1]));
+	}
+	for time in ordering {
+		roundtrip!(Time(time));
+	}
+
+	assert!(deserialize::<Time>(&serialize(&(0i64, 1_000_000_000u32)).unwrap()).is_err());
+}
+
+#[test]
+fn ranges() {
+	expect(0u32..10, &[0, 0, 0, 0, 0, 0, 0, 10]);
+	expect(1u8..=2, &[1, 2]);
+	roundtrip!(0u32..10);
+	roundtrip!(i64::MIN..=i64::MAX);
+	roundtrip!("a".to_owned().."b".to_owned());
+	roundtrip!("".to_owned()..="".to_owned());
+	roundtrip!(vec![("aa".to_owned().."b".to_owned()), ("".to_owned().."z".to_owned())]);
+
+	assert!(serialize(&(0u32..10)).unwrap() < serialize(&(0u32..11)).unwrap());
+	assert!(serialize(&(0u32..10)).unwrap() < serialize(&(1u32..2)).unwrap());
+	assert!(
+		serialize(&("a".to_owned().."z".to_owned())).unwrap()
+			< serialize(&("ab".to_owned().."b".to_owned())).unwrap()
+	);
+}
+
+#[test]
+fn into_slice() {
+	use storekey::serialize_into_slice;
+
+	let value = (42u64, "hello".to_owned(), vec![Some(1u16), None]);
+	let expected = serialize(&value).unwrap();
+
+	let mut buf = [0xaa; 64];
+	let len = serialize_into_slice(&mut buf, &value).unwrap();
+	assert_eq!(len, expected.len());
+	assert_eq!(&buf[..len], &expected[..]);
+	assert!(buf[len..].iter().all(|b| *b == 0xaa));
+
+	let mut offset = len;
+	offset += serialize_into_slice(&mut buf[offset..], &7u8).unwrap();
+	assert_eq!(offset, len + 1);
+	assert_eq!(buf[len], 7);
+
+	let mut small = [0; 4];
+	assert!(serialize_into_slice(&mut small, &value).is_err());
+
+	let mut serializer = storekey::Serializer::new(Vec::new());
+	value.serialize(&mut serializer).unwrap();
+	assert_eq!(serializer.bytes_written(), expected.len());
+	serializer.serialize_var_u64(1 << 20).unwrap();
+	assert_eq!(serializer.bytes_written(), expected.len() + 4);
+}
+
+#[test]
+fn stream() {
+	use storekey::deserialize_iter;
+
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	struct Record {
+		id: u32,
+		name: String,
+	}
+
+	let records = vec![
+		Record {
+			id: 1,
+			name: "one".to_owned(),
+		},
+		Record {
+			id: 2,
+			name: "".to_owned(),
+		},
+		Record {
+			id: 3,
+			name: "three".to_owned(),
+		},
+	];
+	let mut buf = Vec::new();
+	for record in &records {
+		storekey::serialize_into(&mut buf, record).unwrap();
+	}
+
+	let decoded = deserialize_iter(&buf[..]).collect::<Result<Vec<Record>, _>>().unwrap();
+	assert_eq!(decoded, records);
+
+	assert_eq!(deserialize_iter::<_, Record>(&[][..]).count(), 0);
+
+	for len in [buf.len() - 1, buf.len() - 3, buf.len() - 8] {
+		let mut iter = deserialize_iter::<_, Record>(&buf[..len]);
+		assert_eq!(iter.next().unwrap().unwrap(), records[0]);
+		assert_eq!(iter.next().unwrap().unwrap(), records[1]);
+		assert!(iter.next().unwrap().is_err());
+		assert!(iter.next().is_none());
+	}
+}
+
+#[test]
+fn borrowed_slices() {
+	let bytes = serialize("hello").unwrap();
+	let borrowed: &str = deserialize(&bytes).unwrap();
+	assert_eq!(borrowed, "hello");
+	assert_eq!(borrowed.as_ptr(), bytes.as_ptr());
+
+	let bytes = serialize(serde_bytes::Bytes::new(b"he\0llo")).unwrap();
+	let borrowed: &[u8] = deserialize(&bytes).unwrap();
+	assert_eq!(borrowed, b"he\0llo");
+	assert_eq!(borrowed.as_ptr(), bytes[8..].as_ptr());
+
+	// A reader can not lend out its bytes, so borrowed types can only be read from slices.
+	let err = deserialize_from::<_, &str>(&b"hello\0"[..]).unwrap_err();
+	assert!(err.to_string().contains("expected a borrowed string"), "{err}");
+
+	// Strings are not escaped, so a borrowed string ends at the first NUL.
+	assert_eq!(deserialize::<&str>(&serialize("hel\0lo").unwrap()).unwrap(), "hel");
+}
+
+#[test]
+fn error_offset() {
+	use storekey::decode::Error;
+	use storekey::{deserialize_from_with_offset, deserialize_with_offset};
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	enum Kind {
+		A,
+		B(u8),
+	}
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Key {
+		id: u16,
+		kind: Kind,
+	}
+
+	let mut bytes = serialize(&Key {
+		id: 7,
+		kind: Kind::B(1),
+	})
+	.unwrap();
+	bytes[5] = 9;
+
+	let err = deserialize_with_offset::<Key>(&bytes).unwrap_err();
+	assert_eq!(err.offset, 6);
+	assert!(matches!(err.kind, Error::Message(_)), "{err:?}");
+	assert!(err.to_string().ends_with("at byte offset 6"), "{err}");
+
+	let err = deserialize_from_with_offset::<_, Key>(&bytes[..]).unwrap_err();
+	assert_eq!(err.offset, 6);
+
+	let err = deserialize_with_offset::<(u8, String)>(&[1, b'a', b'b']).unwrap_err();
+	assert_eq!(err.offset, 3);
+	let err = deserialize_from_with_offset::<_, (u8, String)>(&[1, b'a', b'b'][..]).unwrap_err();
+	assert_eq!(err.offset, 3);
+
+	let err = deserialize_with_offset::<u32>(&[0, 1]).unwrap_err();
+	assert_eq!(err.offset, 2);
+	let err = deserialize_from_with_offset::<_, u32>(&[0, 1][..]).unwrap_err();
+	assert_eq!(err.offset, 2);
+
+	let err = deserialize_with_offset::<String>(b"abc").unwrap_err();
+	assert_eq!(err.kind, Error::UnexpectedEof);
+	assert!(std::error::Error::source(&err).is_none());
+	assert_eq!(err.to_string(), format!("{} at byte offset 3", err.kind));
+
+	// The plain entry points return the error itself.
+	assert!(matches!(deserialize::<String>(b"abc").unwrap_err(), Error::UnexpectedEof));
+
+	let mut iter = storekey::deserialize_iter::<_, u16>(&[0, 1, 0][..]);
+	assert_eq!(iter.next().unwrap().unwrap(), 1);
+	assert!(iter.next().unwrap().is_err());
+	assert_eq!(iter.offset(), 3);
+}
+
+#[test]
+fn peek() {
+	use storekey::Deserializer;
+
+	let bytes = serialize(&(3u8, 4u16)).unwrap();
+
+	let mut deserializer = Deserializer::from_slice(&bytes);
+	assert_eq!(deserializer.peek_u8().unwrap(), Some(3));
+	assert_eq!(deserializer.peek_u8().unwrap(), Some(3));
+	assert_eq!(u8::deserialize(&mut deserializer).unwrap(), 3);
+	assert_eq!(deserializer.peek_u8().unwrap(), Some(0));
+	assert_eq!(u16::deserialize(&mut deserializer).unwrap(), 4);
+	assert_eq!(deserializer.peek_u8().unwrap(), None);
+
+	let mut deserializer = Deserializer::from_reader(&bytes[..]);
+	assert_eq!(deserializer.peek_u8().unwrap(), Some(3));
+	assert_eq!(<(u8, u16)>::deserialize(&mut deserializer).unwrap(), (3, 4));
+	assert_eq!(deserializer.peek_u8().unwrap(), None);
+}
+
+#[test]
+fn large_tuples() {
+	let tuple = (
+		1u8,
+		-2i16,
+		"three".to_owned(),
+		4u32,
+		vec![5u64, 6],
+		Some(7i64),
+		'8',
+		"".to_owned(),
+		9.5f64,
+		vec!["ten".to_owned()],
+	);
+	roundtrip!(tuple);
+
+	let mut larger = tuple.clone();
+	larger.2 = "threee".to_owned();
+	less(tuple.clone(), larger);
+
+	roundtrip!((0u8, 1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8, 10u8, 11u8));
+}
+
+#[test]
+fn shared_pointers() {
+	use std::rc::Rc;
+	use std::sync::Arc;
+
+	let string: Arc<str> = Arc::from("hello");
+	assert_eq!(serialize(&string).unwrap(), serialize("hello").unwrap());
+	assert_eq!(deserialize::<Arc<str>>(&serialize(&string).unwrap()).unwrap(), string);
+
+	let bytes: Arc<[u8]> = Arc::from(&[5u8, 6, 7][..]);
+	assert_eq!(serialize(&bytes).unwrap(), serialize(&vec![5u8, 6, 7]).unwrap());
+	assert_eq!(deserialize::<Arc<[u8]>>(&serialize(&bytes).unwrap()).unwrap(), bytes);
+
+	roundtrip!(Rc::new(42u64));
+	roundtrip!(Arc::new("shared".to_owned()));
+	roundtrip!(Rc::<str>::from(""));
+
+	let boxed: Box<[u64]> = vec![1, u64::MAX].into_boxed_slice();
+	assert_eq!(serialize(&boxed).unwrap(), serialize(&vec![1, u64::MAX]).unwrap());
+	roundtrip!(boxed);
+	roundtrip!(Arc::<[String]>::from(vec!["a".to_owned(), "".to_owned()]));
+	less(Arc::<[String]>::from(vec!["a".to_owned()]), Arc::from(vec!["b".to_owned()]));
+
+	let mut value = 7u16;
+	assert_eq!(serialize(&&mut value).unwrap(), serialize(&7u16).unwrap());
+}
+
+#[test]
+fn phantom_data() {
+	use std::marker::PhantomData;
+
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	struct Keyed<T> {
+		id: u64,
+		_marker: PhantomData<T>,
+	}
+
+	expect(PhantomData::<String>, &[]);
+
+	let keyed = Keyed::<String> {
+		id: 5,
+		_marker: PhantomData,
+	};
+	assert_eq!(serialize(&keyed).unwrap(), serialize(&5u64).unwrap());
+	roundtrip!(keyed);
+}
+
+#[test]
+fn compare_encoded() {
+	use std::cmp::Ordering;
+	use storekey::cmp_encoded;
+
+	// A small xorshift generator, to avoid depending on a random number crate.
+	let mut state = 0x2545_f491_4f6c_dd1du64;
+	let mut next = move || {
+		state ^= state << 13;
+		state ^= state >> 7;
+		state ^= state << 17;
+		state
+	};
+
+	for _ in 0..1000 {
+		let (a, b) = (next(), next() >> (next() % 64));
+		assert_eq!(cmp_encoded(&serialize(&a).unwrap(), &serialize(&b).unwrap()), a.cmp(&b));
+
+		let mut string = || -> String {
+			let len = next() % 6;
+			(0..len).map(|_| char::from(b'a' + (next() % 3) as u8)).collect()
+		};
+		let (a, b) = (string(), string());
+		assert_eq!(cmp_encoded(&serialize(&a).unwrap(), &serialize(&b).unwrap()), a.cmp(&b));
+	}
+
+	assert_eq!(cmp_encoded(&serialize(&1u8).unwrap(), &serialize(&1u8).unwrap()), Ordering::Equal);
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn order_preserving_helper() {
+	use storekey::test_util::{assert_order_preserving, assert_roundtrip};
+
+	assert_order_preserving(&1u32, &2u32);
+	assert_order_preserving(&"b", &"a");
+	assert_order_preserving(&-1.0f64, &-1.0f64);
+	assert_order_preserving(&f64::NAN, &1.0);
+	assert_order_preserving(&(1u32, "a"), &(0u32, "b"));
+
+	assert_roundtrip(&Tagged(vec![Some("a".to_owned()), None]));
+	assert_roundtrip(&(-1i8, 2.5f32));
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Lossy(#[serde(with = "lossy_u16")] u16);
+
+	mod lossy_u16 {
+		pub fn serialize<S: serde::Serializer>(v: &u16, s: S) -> Result<S::Ok, S::Error> {
+			s.serialize_u16(*v)
+		}
+
+		pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<u16, D::Error> {
+			<u8 as serde::Deserialize>::deserialize(d).map(u16::from)
+		}
+	}
+
+	let result = std::panic::catch_unwind(|| assert_roundtrip(&Lossy(7)));
+	assert!(result.is_err());
+}
+
+#[test]
+fn nested_sequences() {
+	use std::collections::BTreeMap;
+	use storekey::Reverse;
+
+	// Untagged elements are written back to back, so one starting with the `0x01` terminator
+	// ends the sequence early.
+	expect(Vec::<u8>::new(), &[1]);
+	expect(vec![0u8, 2], &[0, 2, 1]);
+	expect(vec![vec![0u8]], &[0, 1, 1]);
+	assert_eq!(serialize(&vec![Vec::<u8>::new()]).unwrap(), [1, 1]);
+	assert_eq!(deserialize::<Vec<Vec<u8>>>(&[1, 1]).unwrap(), Vec::<Vec<u8>>::new());
+	assert_eq!(deserialize::<Vec<u8>>(&serialize(&vec![0u8, 1, 2]).unwrap()).unwrap(), [0]);
+
+	expect(Tagged(Vec::<u8>::new()), &[1]);
+	expect(Tagged(vec![1u8]), &[2, 1, 1]);
+	expect(Tagged(vec![Vec::<u8>::new()]), &[2, 1, 1]);
+	expect(Tagged(vec![vec![0u8]]), &[2, 2, 0, 1, 1]);
+	expect((Tagged(vec![1u8]), vec![2u8]), &[2, 1, 1, 2, 1]);
+
+	roundtrip!(Tagged(vec![0u8, 1, 2, 1, 0]));
+	roundtrip!(Tagged(vec![1u32, 0x0100_0000, 0]));
+	roundtrip!(Tagged(vec![Vec::<u8>::new(), vec![], vec![0], vec![1], vec![]]));
+	roundtrip!(Tagged(vec![vec![vec![]], vec![vec![0u8, 1], vec![]], vec![]]));
+	roundtrip!(Tagged(vec!["".to_owned(), "a".to_owned(), "".to_owned(), "".to_owned()]));
+	roundtrip!(Reverse(Tagged(vec![vec![1u8], vec![]])));
+
+	let mut map = BTreeMap::new();
+	map.insert("".to_owned(), Vec::<u8>::new());
+	map.insert("a".to_owned(), vec![1, 0, 1]);
+	map.insert("b".to_owned(), vec![]);
+	let map = Tagged(map);
+	roundtrip!(map);
+	roundtrip!(Tagged(BTreeMap::<String, Vec<u8>>::new()));
+	roundtrip!(Tagged(BTreeMap::from([(Vec::<u8>::new(), Vec::<u8>::new())])));
+
+	less(Tagged(Vec::<Vec<u8>>::new()), Tagged(vec![vec![]]));
+	less(Tagged(vec![vec![]]), Tagged(vec![vec![0u8]]));
+	less(Tagged(vec![vec![0u8]]), Tagged(vec![vec![0u8], vec![]]));
+	less(Tagged(vec![1u32]), Tagged(vec![1u32, 2]));
+	less(Tagged(vec![1u32, 2]), Tagged(vec![2u32]));
+	less(Tagged(vec!["".to_owned()]), Tagged(vec!["".to_owned(), "".to_owned()]));
+	less(Tagged(vec!["a".to_owned()]), Tagged(vec!["a".to_owned(), "".to_owned()]));
+	less(Tagged(vec!["a".to_owned(), "b".to_owned()]), Tagged(vec!["ab".to_owned()]));
+	less(Reverse(Tagged(vec![vec![0u8]])), Reverse(Tagged(vec![vec![]])));
+
+	assert!(deserialize::<Tagged<Vec<u8>>>(&[3]).is_err());
+}
+
+#[test]
+fn newtypes() {
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	struct UserId(u64);
+
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	#[serde(transparent)]
+	struct Name {
+		inner: String,
+	}
+
+	assert_eq!(serialize(&UserId(5)).unwrap(), serialize(&5u64).unwrap());
+	assert_eq!(
+		serialize(&Name {
+			inner: "a".to_owned()
+		})
+		.unwrap(),
+		serialize("a").unwrap()
+	);
+	roundtrip!(UserId(u64::MAX));
+	roundtrip!(Name {
+		inner: "name".to_owned()
+	});
+}
+
+#[test]
+fn c_strings() {
+	use std::ffi::CString;
+
+	let hello = CString::new("hello").unwrap();
+	expect(hello.clone(), &[0, 0, 0, 0, 0, 0, 0, 5, b'h', b'e', b'l', b'l', b'o']);
+	roundtrip!(hello);
+	roundtrip!(CString::new(vec![0xff, 0x01]).unwrap());
+	roundtrip!(CString::default());
+
+	less(CString::new("abc").unwrap(), CString::new("abd").unwrap());
+	// Byte buffers are length prefixed, so shorter strings sort first.
+	assert!(
+		serialize(&CString::new("b").unwrap()).unwrap()
+			< serialize(&CString::new("ab").unwrap()).unwrap()
+	);
+
+	assert!(deserialize::<CString>(&[0, 0, 0, 0, 0, 0, 0, 2, b'a', 0]).is_err());
+}
+
+#[test]
+fn wrapping() {
+	use std::num::Wrapping;
+
+	expect(Wrapping(u64::MAX), &[0xff; 8]);
+	assert_eq!(serialize(&Wrapping(-5i32)).unwrap(), serialize(&-5i32).unwrap());
+	roundtrip!(Wrapping(u64::MAX));
+	roundtrip!(Wrapping(i8::MIN));
+	less(Wrapping(-1i64), Wrapping(1i64));
+}
+
+#[test]
+fn size() {
+	use std::collections::BTreeMap;
+	use storekey::{serialize_with_capacity, serialized_size};
+
+	#[derive(Serialize)]
+	struct Mixed {
+		id: u64,
+		name: String,
+		tags: Vec<String>,
+		data: Vec<u8>,
+		parent: Option<Box<Mixed>>,
+		meta: BTreeMap<u8, (char, f32)>,
+	}
+
+	let value = Mixed {
+		id: 1,
+		name: "mixed".to_owned(),
+		tags: vec!["".to_owned(), "a".to_owned()],
+		data: vec![0, 1, 0, 2],
+		parent: Some(Box::new(Mixed {
+			id: 2,
+			name: "".to_owned(),
+			tags: vec![],
+			data: vec![],
+			parent: None,
+			meta: BTreeMap::new(),
+		})),
+		meta: [(1, ('é', 1.0))].into_iter().collect(),
+	};
+
+	assert_eq!(serialized_size(&value).unwrap(), serialize(&value).unwrap().len());
+	assert_eq!(serialized_size(&()).unwrap(), 0);
+	assert_eq!(serialized_size("abc").unwrap(), 4);
+	assert!(serialized_size(&'\0').is_err());
+
+	let tags: Vec<String> = (0..1000).map(|i| i.to_string()).collect();
+	let size = serialized_size(&tags).unwrap();
+	let bytes = serialize_with_capacity(size, &tags).unwrap();
+	assert_eq!(bytes.len(), size);
+	assert_eq!(bytes, serialize(&tags).unwrap());
+}
+
+#[cfg(unix)]
+#[test]
+fn os_strings() {
+	use std::ffi::OsString;
+	use std::os::unix::ffi::OsStringExt;
+
+	let invalid = OsString::from_vec(vec![b'a', 0xff, 0, 1, b'b']);
+	assert!(invalid.to_str().is_none());
+	let invalid = Tagged(invalid);
+	roundtrip!(invalid);
+	roundtrip!(OsString::new());
+
+	less(OsString::from("a"), OsString::from("b"));
+	less(OsString::from("a"), OsString::from("aa"));
+	less(OsString::from_vec(vec![0x7f]), OsString::from_vec(vec![0xff]));
+}
+
+#[test]
+fn paths() {
+	use std::path::{Path, PathBuf};
+
+	let a = serialize(Path::new("a/b")).unwrap();
+	let b = serialize(Path::new("a/b/")).unwrap();
+	assert_ne!(a, b);
+	assert_eq!(a, serialize("a/b").unwrap());
+	assert_eq!(deserialize::<PathBuf>(&a).unwrap().as_os_str(), "a/b");
+	assert_eq!(deserialize::<PathBuf>(&b).unwrap().as_os_str(), "a/b/");
+	assert_eq!(deserialize::<&Path>(&b).unwrap().as_os_str(), "a/b/");
+	roundtrip!(PathBuf::from("/tmp//x/./y"));
+
+	less(PathBuf::from("a/b"), PathBuf::from("a/c"));
+
+	#[cfg(unix)]
+	{
+		use std::ffi::OsString;
+		use std::os::unix::ffi::OsStringExt;
+		assert!(serialize(&PathBuf::from(OsString::from_vec(vec![0xff]))).is_err());
+	}
+}
+
+#[test]
+fn segments() {
+	use std::borrow::Cow;
+	use storekey::Deserializer;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Key<'a> {
+		id: u32,
+		#[serde(borrow)]
+		name: Cow<'a, str>,
+		#[serde(borrow)]
+		tags: Vec<Cow<'a, str>>,
+		data: serde_bytes::ByteBuf,
+	}
+
+	let value = Key {
+		id: 0x01020304,
+		name: Cow::Borrowed("segmented"),
+		tags: vec![Cow::Borrowed("a"), Cow::Borrowed("bc")],
+		data: serde_bytes::ByteBuf::from(vec![0, 1, 2, 3]),
+	};
+	let bytes = serialize(&value).unwrap();
+
+	// Every way of splitting the input into three segments, including empty ones.
+	for i in 0..=bytes.len() {
+		for j in i..=bytes.len() {
+			let segments = [&bytes[..i], &bytes[i..j], &bytes[j..]];
+			let mut deserializer = Deserializer::from_segments(&segments);
+			let decoded = Key::deserialize(&mut deserializer).unwrap();
+			assert_eq!(decoded, value);
+			assert!(deserializer.peek_u8().unwrap().is_none());
+		}
+	}
+
+	// Strings within a segment are borrowed, and strings spanning a boundary are copied.
+	let segments = [&bytes[..6], &bytes[6..]];
+	let decoded = Key::deserialize(&mut Deserializer::from_segments(&segments)).unwrap();
+	assert!(matches!(decoded.name, Cow::Owned(_)));
+	let segments = [&bytes[..14], &bytes[14..]];
+	let decoded = Key::deserialize(&mut Deserializer::from_segments(&segments)).unwrap();
+	assert_eq!(decoded.name.as_ptr(), bytes[4..].as_ptr());
+
+	let segments = [&bytes[..bytes.len() - 1]];
+	assert!(Key::deserialize(&mut Deserializer::from_segments(&segments)).is_err());
+}
+
+#[test]
+fn sorted_map() {
+	use std::collections::BTreeMap;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Index(#[serde(with = "storekey::sorted_map")] BTreeMap<u8, String>);
+
+	let index = Index(BTreeMap::from([(3, "a".to_owned()), (4, "b".to_owned())]));
+	let bytes = serialize(&index).unwrap();
+	assert_eq!(bytes, serialize(&index.0).unwrap());
+	assert_eq!(deserialize::<Index>(&bytes).unwrap(), index);
+
+	let duplicate = [3, b'a', 0, 3, b'b', 0, 1];
+	let out_of_order = [4, b'b', 0, 3, b'a', 0, 1];
+	for bytes in [duplicate, out_of_order] {
+		assert!(deserialize::<BTreeMap<u8, String>>(&bytes).is_ok());
+		let err = deserialize::<Index>(&bytes).unwrap_err();
+		assert!(err.to_string().contains("strictly ascending"), "{err}");
+	}
+}
+
+#[test]
+fn hash_map() {
+	use std::collections::HashMap;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Index(#[serde(with = "storekey::hash_map")] HashMap<u8, String>);
+
+	let index = Index(HashMap::from([(3, "a".to_owned())]));
+	let bytes = serialize(&index).unwrap();
+	assert_eq!(bytes, serialize(&index.0).unwrap());
+	assert_eq!(deserialize::<Index>(&bytes).unwrap(), index);
+
+	let out_of_order = [4, b'b', 0, 3, b'a', 0, 1];
+	assert_eq!(deserialize::<Index>(&out_of_order).unwrap().0.len(), 2);
+
+	let duplicate = [3, b'a', 0, 3, b'b', 0, 1];
+	assert_eq!(deserialize::<HashMap<u8, String>>(&duplicate).unwrap()[&3], "b");
+	let err = deserialize::<Index>(&duplicate).unwrap_err();
+	assert!(err.to_string().contains("duplicate map key"), "{err}");
+}
+
+#[test]
+fn iterators() {
+	use storekey::serialize_iter_into;
+
+	let mut bytes = Vec::new();
+	serialize_iter_into(&mut bytes, (1u16..5).map(|i| i * 3)).unwrap();
+	assert_eq!(bytes, serialize(&vec![3u16, 6, 9, 12]).unwrap());
+	assert_eq!(deserialize::<Vec<u16>>(&bytes).unwrap(), [3, 6, 9, 12]);
+
+	// Iterators without an exact size hint produce the same output.
+	let mut bytes = Vec::new();
+	serialize_iter_into(&mut bytes, ["a", "", "bc"].iter().filter(|s| s.len() < 10)).unwrap();
+	assert_eq!(bytes, serialize(&["a", "", "bc"][..]).unwrap());
+
+	let mut bytes = Vec::new();
+	serialize_iter_into(&mut bytes, std::iter::empty::<u8>()).unwrap();
+	assert_eq!(bytes, [1]);
+}
+
+#[test]
+fn error_equality() {
+	use storekey::decode::Error;
+
+	assert_eq!(
+		deserialize::<u32>(&[0, 1]).unwrap_err(),
+		Error::Io(std::io::ErrorKind::UnexpectedEof.into())
+	);
+	assert_eq!(
+		deserialize_from::<_, u32>(&[0, 1][..]).unwrap_err(),
+		Error::Io(std::io::ErrorKind::UnexpectedEof.into())
+	);
+	assert_eq!(deserialize::<String>(b"abc").unwrap_err(), Error::UnexpectedEof);
+	assert_eq!(deserialize::<String>(&[0xff, 0]).unwrap_err(), Error::InvalidUtf8);
+	assert_ne!(deserialize::<String>(&[0xff, 0]).unwrap_err(), Error::UnexpectedEof);
+	assert_eq!(
+		deserialize::<Option<u8>>(&[2, 5]).unwrap_err(),
+		deserialize_from::<_, Option<u8>>(&[2, 5][..]).unwrap_err()
+	);
+	assert_ne!(Error::UnexpectedEof, Error::InvalidUtf8);
+	assert_eq!(Error::Message("a".to_owned()), Error::Message("a".to_owned()));
+	assert_ne!(Error::Message("a".to_owned()), Error::Message("b".to_owned()));
+	assert_eq!(
+		Error::Io(std::io::ErrorKind::Other.into()),
+		Error::Io(std::io::Error::other("other"))
+	);
+
+	assert_eq!(
+		serialize(&'\0').unwrap_err(),
+		storekey::encode::Error::Message("cannot serialize NUL char".to_owned())
+	);
+}
+
+#[test]
+fn debug_dump() {
+	use storekey::debug_dump;
+
+	assert_eq!(debug_dump(&[]), "");
+	assert_eq!(debug_dump(&serialize(&vec!["a", "b c"]).unwrap()), r#""a" 00 "b c" 00 01"#);
+	assert_eq!(debug_dump(&serialize(&(0x41u8, 0x42u8)).unwrap()), "41 42");
+	assert_eq!(debug_dump(&serialize("é").unwrap()), "c3 a9 00");
+	assert_eq!(debug_dump(&serialize(&Some("\"")).unwrap()), r#"01 "\"" 00"#);
+}
+
+#[test]
+fn lossy() {
+	use std::collections::BTreeMap;
+	use storekey::deserialize_lossy;
+
+	let values = vec![1u32, 2, 3, 4];
+	let bytes = serialize(&values).unwrap();
+
+	// Truncated sequences are an error by default.
+	for len in 0..bytes.len() {
+		assert!(deserialize::<Vec<u32>>(&bytes[..len]).is_err(), "{len}");
+	}
+
+	// Each element takes four bytes.
+	assert_eq!(deserialize_lossy::<Vec<u32>>(&bytes).unwrap(), values);
+	assert_eq!(deserialize_lossy::<Vec<u32>>(&bytes[..bytes.len() - 1]).unwrap(), values);
+	assert_eq!(deserialize_lossy::<Vec<u32>>(&bytes[..11]).unwrap(), [1, 2]);
+	assert_eq!(deserialize_lossy::<Vec<u32>>(&bytes[..8]).unwrap(), [1, 2]);
+	assert_eq!(deserialize_lossy::<Vec<u32>>(&[]).unwrap(), []);
+
+	let nested =
+		serialize(&vec![vec!["a".to_owned(), "bc".to_owned()], vec!["d".to_owned()]]).unwrap();
+	let recovered = deserialize_lossy::<Vec<Vec<String>>>(&nested[..4]).unwrap();
+	assert_eq!(recovered, [["a"]]);
+
+	// Map entries truncated in either the key or the value are dropped.
+	let map = BTreeMap::from([(1u16, "ab".to_owned()), (2, "cd".to_owned())]);
+	let bytes = serialize(&map).unwrap();
+	assert_eq!(bytes.len(), 11);
+	assert!(deserialize::<BTreeMap<u16, String>>(&bytes[..10]).is_err());
+	assert_eq!(deserialize_lossy::<BTreeMap<u16, String>>(&bytes[..10]).unwrap(), map);
+	let first = BTreeMap::from([(1u16, "ab".to_owned())]);
+	for len in [5, 6, 7, 9] {
+		assert_eq!(deserialize_lossy::<BTreeMap<u16, String>>(&bytes[..len]).unwrap(), first);
+	}
+	assert_eq!(deserialize_lossy::<BTreeMap<u16, String>>(&bytes[..4]).unwrap(), BTreeMap::new());
+
+	// Corruption other than truncation is still an error.
+	let mut corrupt = bytes.clone();
+	corrupt[2] = 0xff;
+	assert!(deserialize_lossy::<BTreeMap<u16, String>>(&corrupt).is_err());
+}
+
+#[test]
+fn flush() {
+	use std::io::{self, BufWriter, Write};
+	use storekey::Serializer;
+
+	#[derive(Default)]
+	struct Flushes {
+		bytes: Vec<u8>,
+		flushes: usize,
+	}
+
+	impl Write for Flushes {
+		fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+			self.bytes.write(buf)
+		}
+
+		fn flush(&mut self) -> io::Result<()> {
+			self.flushes += 1;
+			Ok(())
+		}
+	}
+
+	let mut inner = Flushes::default();
+	let mut buffered = BufWriter::new(&mut inner);
+	let mut serializer = Serializer::new(&mut buffered);
+	("key", 1u8).serialize(&mut serializer).unwrap();
+	assert!(buffered.get_ref().bytes.is_empty());
+
+	let mut serializer = Serializer::new(&mut buffered);
+	serializer.flush().unwrap();
+	assert_eq!(buffered.get_ref().bytes, serialize(&("key", 1u8)).unwrap());
+	assert_eq!(buffered.get_ref().flushes, 1);
+}
+
+#[test]
+fn byte_wrappers() {
+	use storekey::{ByteSlice, ByteString};
+
+	let raw = vec![b'k', 0, 1, 0xff, b'"'];
+	let bytes = serialize(&ByteString(raw.clone())).unwrap();
+	assert_eq!(bytes, serialize(serde_bytes::Bytes::new(&raw)).unwrap());
+	assert_eq!(serialize(&ByteSlice(&raw)).unwrap(), bytes);
+	roundtrip!(ByteString(raw.clone()));
+	roundtrip!(ByteString(vec![]));
+	assert_eq!(deserialize::<ByteSlice>(&bytes).unwrap(), ByteSlice(&raw));
+	assert_eq!(deserialize_from::<_, ByteString>(&bytes[..]).unwrap(), ByteString(raw.clone()));
+	assert!(serialize(&ByteString(vec![1])).unwrap() < serialize(&ByteString(vec![1, 0])).unwrap());
+
+	// Byte buffers are length-prefixed rather than encoded like a `Vec<u8>`.
+	assert_eq!(serialize(&ByteString(vec![7])).unwrap(), [0, 0, 0, 0, 0, 0, 0, 1, 7]);
+	assert_eq!(serialize(&vec![7u8]).unwrap(), [7, 1]);
+	assert!(deserialize::<ByteString>(&serialize(&raw).unwrap()).is_err());
+	assert_ne!(deserialize::<Vec<u8>>(&bytes).unwrap(), raw);
+	assert!(serialize(&ByteString(vec![2])).unwrap() < serialize(&ByteString(vec![1, 1])).unwrap());
+	assert!(serialize(&vec![2u8]).unwrap() > serialize(&vec![1u8, 1]).unwrap());
+
+	assert_eq!(format!("{:?}", ByteString(raw.clone())), r#"b"k\x00\x01\xff\"""#);
+	assert_eq!(format!("{:?}", ByteSlice(b"a b")), r#"b"a b""#);
+	assert_eq!(format!("{:?}", (1, ByteSlice(&[]))), r#"(1, b"")"#);
+}
+
+#[test]
+fn checkpoints() {
+	use storekey::Deserializer;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	enum Old {
+		A(u8),
+	}
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	enum New {
+		A(u8),
+		B(String),
+	}
+
+	let mut bytes = serialize(&New::B("b".to_owned())).unwrap();
+	bytes.extend(serialize(&7u8).unwrap());
+
+	let mut deserializer = Deserializer::from_slice(&bytes);
+	let start = deserializer.checkpoint();
+	assert!(Old::deserialize(&mut deserializer).is_err());
+	deserializer.restore(start);
+	assert_eq!(New::deserialize(&mut deserializer).unwrap(), New::B("b".to_owned()));
+
+	let before = deserializer.checkpoint();
+	assert_eq!(u8::deserialize(&mut deserializer).unwrap(), 7);
+	assert_eq!(deserializer.peek_u8().unwrap(), None);
+	deserializer.restore(before);
+	assert_eq!(deserializer.peek_u8().unwrap(), Some(7));
+}
+
+#[test]
+fn monotonic() {
+	use std::time::{Duration, Instant};
+	use storekey::monotonic::MonotonicNanos;
+
+	let base = Instant::now();
+	let times = [base, base + Duration::from_nanos(1), base + Duration::from_secs(1)];
+	let nanos: Vec<_> = times.iter().map(|t| MonotonicNanos::since(base, *t)).collect();
+	assert_eq!(nanos, [MonotonicNanos(0), MonotonicNanos(1), MonotonicNanos(1_000_000_000)]);
+	for pair in nanos.windows(2) {
+		less(pair[0], pair[1]);
+	}
+	for (n, t) in nanos.iter().zip(times) {
+		roundtrip!(*n);
+		assert_eq!(n.to_instant(base), Some(t));
+	}
+	expect(MonotonicNanos(258), &[0, 0, 0, 0, 0, 0, 1, 2]);
+
+	let earlier = base.checked_sub(Duration::from_secs(1)).unwrap_or(base);
+	assert_eq!(MonotonicNanos::since(base, earlier), MonotonicNanos(0));
+}
+
+#[test]
+fn canonical_nan() {
+	use storekey::Serializer;
+
+	fn canonical<T: Serialize>(v: T) -> Vec<u8> {
+		let mut bytes = Vec::new();
+		v.serialize(&mut Serializer::new(&mut bytes).canonical_nan()).unwrap();
+		bytes
+	}
+
+	let nans =
+		[f64::NAN, -f64::NAN, f64::from_bits(0x7ff0_0000_0000_0001), f64::from_bits(u64::MAX)];
+	for nan in nans {
+		assert!(nan.is_nan());
+		assert_eq!(canonical(nan), serialize(&f64::NAN).unwrap());
+		assert_eq!(canonical(nan as f32), serialize(&f32::NAN).unwrap());
+		assert!(canonical(nan) > canonical(f64::INFINITY));
+	}
+	assert_ne!(serialize(&nans[0]).unwrap(), serialize(&nans[1]).unwrap());
+
+	assert_eq!(canonical(-0.0f64), serialize(&-0.0f64).unwrap());
+	assert_eq!(
+		canonical((1.5f32, vec![f64::NEG_INFINITY])),
+		serialize(&(1.5f32, vec![f64::NEG_INFINITY])).unwrap()
+	);
+	assert_eq!(
+		canonical(storekey::Reverse(-f64::NAN)),
+		serialize(&storekey::Reverse(f64::NAN)).unwrap()
+	);
+}
+
+#[test]
+fn uninhabited() {
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	enum Never {}
+
+	roundtrip!(Ok::<u8, Never>(5));
+	expect(Ok::<u8, Never>(5), &[0, 0, 0, 0, 5]);
+	assert!(deserialize::<Never>(&[0, 0, 0, 0]).is_err());
+	assert!(deserialize::<Result<u8, Never>>(&[0, 0, 0, 1, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn checksums() {
+	use storekey::checksum::{crc32, verify, ChecksumWriter};
+	use storekey::Serializer;
+
+	assert_eq!(crc32(b""), 0);
+	assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+
+	let value = (42u64, "checksummed".to_owned(), vec![1.5f32]);
+	let mut writer = ChecksumWriter::new(Vec::new());
+	value.serialize(&mut Serializer::new(&mut writer)).unwrap();
+	let (mut bytes, crc) = writer.finish();
+	assert_eq!(bytes, serialize(&value).unwrap());
+	assert_eq!(crc, crc32(&bytes));
+	bytes.extend_from_slice(&crc.to_be_bytes());
+
+	assert_eq!(verify(&bytes), Some(&bytes[..bytes.len() - 4]));
+	for i in 0..bytes.len() {
+		let mut corrupt = bytes.clone();
+		corrupt[i] ^= 0x10;
+		assert_eq!(verify(&corrupt), None, "{i}");
+	}
+	assert_eq!(verify(&[0, 0, 0]), None);
+}
+
+#[test]
+fn remaining() {
+	use storekey::Deserializer;
+
+	let mut bytes = serialize(&(7u16, "prefix")).unwrap();
+	bytes.extend_from_slice(b"\x00\x01 raw tail");
+
+	let mut deserializer = Deserializer::from_slice(&bytes);
+	assert_eq!(deserializer.remaining(), bytes.len());
+	let prefix = <(u16, &str)>::deserialize(&mut deserializer).unwrap();
+	assert_eq!(prefix, (7, "prefix"));
+	assert_eq!(deserializer.remaining(), 11);
+	let tail: &[u8] = deserializer.as_slice();
+	assert_eq!(tail, b"\x00\x01 raw tail");
+	assert_eq!(tail.as_ptr(), bytes[bytes.len() - 11..].as_ptr());
+}
+
+#[test]
+fn recursive_enums() {
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	enum Tree<T> {
+		Leaf(T),
+		Node(Box<Tree<T>>, Box<Tree<T>>),
+	}
+
+	let leaf = |v| Box::new(Tree::Leaf(v));
+	let tree = Tree::Node(leaf(1u32), Box::new(Tree::Node(leaf(2), leaf(3))));
+	roundtrip!(tree);
+	expect(
+		Tree::Node(Box::new(Tree::Leaf(1u8)), Box::new(Tree::Leaf(2))),
+		&[0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 2],
+	);
+}
+
+#[test]
+fn durations() {
+	use std::time::Duration;
+
+	expect(Duration::new(1, 2), &[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 2]);
+	roundtrip!(Duration::MAX);
+	less(Duration::new(0, 999_999_999), Duration::new(1, 0));
+	less(Duration::new(1, 0), Duration::new(1, 1));
+
+	roundtrip!(Duration::new(1, 999_999_999));
+
+	for (secs, nanos) in [(1u64, 1_000_000_000u32), (1, 1_500_000_000), (u64::MAX, u32::MAX)] {
+		let bytes = serialize(&(secs, nanos)).unwrap();
+		let err = deserialize::<Duration>(&bytes).unwrap_err();
+		assert!(err.to_string().contains("nanoseconds out of range"), "{err}");
+		let err = deserialize_from::<_, Duration>(&bytes[..]).unwrap_err();
+		assert!(err.to_string().contains("nanoseconds out of range"), "{err}");
+	}
+}
+
+#[test]
+fn successors() {
+	use storekey::successor;
+
+	assert_eq!(successor(&[1, 2, 3]), Some(vec![1, 2, 4]));
+	assert_eq!(successor(&[1, 2, 0xff]), Some(vec![1, 3]));
+	assert_eq!(successor(&[0xfe, 0xff, 0xff]), Some(vec![0xff]));
+	assert_eq!(successor(&[0]), Some(vec![1]));
+	assert_eq!(successor(&[0xff, 0xff]), None);
+	assert_eq!(successor(&[]), None);
+
+	let prefix = serialize(&(u8::MAX, u16::MAX)).unwrap();
+	assert_eq!(successor(&prefix), None);
+
+	let prefix = serialize(&(1u8, "a")).unwrap();
+	let end = successor(&prefix).unwrap();
+	for key in [(1u8, "a", 0u64), (1, "a", u64::MAX)] {
+		let key = serialize(&key).unwrap();
+		assert!(prefix <= key && key < end);
+	}
+	for key in [(1u8, "b", 0u64), (2, "", 0), (1, "", u64::MAX)] {
+		let key = serialize(&key).unwrap();
+		assert!(key < prefix || key >= end);
+	}
+}
+
+#[test]
+fn pointer_sized() {
+	expect(5usize, &[0, 0, 0, 0, 0, 0, 0, 5]);
+	expect(-1isize, &[0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+	assert_eq!(serialize(&usize::MAX).unwrap(), serialize(&(usize::MAX as u64)).unwrap());
+	assert_eq!(serialize(&isize::MIN).unwrap(), serialize(&(isize::MIN as i64)).unwrap());
+	roundtrip!(usize::MAX);
+	roundtrip!(isize::MIN);
+	less(0usize, usize::MAX);
+	less(isize::MIN, isize::MAX);
+
+	#[cfg(target_pointer_width = "64")]
+	assert_eq!(deserialize::<usize>(&serialize(&u64::MAX).unwrap()).unwrap(), usize::MAX);
+	#[cfg(target_pointer_width = "32")]
+	assert!(deserialize::<usize>(&serialize(&u64::MAX).unwrap()).is_err());
+}
+
+#[test]
+fn zero_width() {
+	use std::collections::BTreeMap;
+
+	expect(Some(()), &[1]);
+	expect(None::<()>, &[0]);
+	expect(Vec::<()>::new(), &[1]);
+	expect(Tagged(vec![(); 3]), &[2, 2, 2, 1]);
+	expect(Tagged(BTreeMap::from([(1u8, ()), (2, ())])), &[2, 1, 2, 2, 1]);
+	roundtrip!(Tagged(vec![(); 3]));
+	roundtrip!(Tagged(vec![Some(()), None]));
+	roundtrip!(Tagged(BTreeMap::from([(1u8, ()), (2, ())])));
+	less(Tagged(vec![(); 2]), Tagged(vec![(); 3]));
+
+	// Untagged zero-width elements do not encode the length, and an element which consumes no
+	// input is an error rather than an endless loop.
+	assert_eq!(serialize(&vec![(); 3]).unwrap(), [1]);
+	let err = deserialize::<Vec<()>>(&[0]).unwrap_err();
+	assert!(err.to_string().contains("consumed no input"), "{err}");
+	let err = deserialize_from::<_, BTreeMap<(), ()>>(&[0][..]).unwrap_err();
+	assert!(err.to_string().contains("consumed no input"), "{err}");
+
+	// A missing terminator or tag is an error.
+	assert!(deserialize::<Tagged<Vec<()>>>(&[2, 2, 2]).is_err());
+	assert!(deserialize::<Tagged<Vec<()>>>(&[2, 0]).is_err());
+
+	#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+	struct Marker;
+
+	roundtrip!(Tagged(vec![Marker; 4]));
+	roundtrip!(Tagged(vec![std::marker::PhantomData::<u64>; 2]));
+
+	let mut many = vec![2; 100_000];
+	assert!(deserialize::<Tagged<Vec<Marker>>>(&many).is_err());
+	many.push(1);
+	assert_eq!(deserialize::<Tagged<Vec<Marker>>>(&many).unwrap().0.len(), 100_000);
+}
+
+#[test]
+fn io_errors() {
+	use std::io::{self, ErrorKind};
+
+	fn read_key(bytes: &[u8]) -> io::Result<(u32, String)> {
+		Ok(deserialize(bytes)?)
+	}
+
+	assert_eq!(read_key(&serialize(&(1u32, "a")).unwrap()).unwrap(), (1, "a".to_owned()));
+	assert_eq!(read_key(&[0, 0]).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+	assert_eq!(read_key(&[0, 0, 0, 1, b'a']).unwrap_err().kind(), ErrorKind::UnexpectedEof);
+	let err = read_key(&[0, 0, 0, 1, 0xff, 0]).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+	let err = io::Error::from(storekey::deserialize_with_offset::<String>(&[0xff, 0]).unwrap_err());
+	assert_eq!(err.kind(), ErrorKind::InvalidData);
+	assert!(err.to_string().ends_with("at byte offset 2"), "{err}");
+
+	let err = io::Error::from(serialize(&'\0').unwrap_err());
+	assert_eq!(err.kind(), ErrorKind::InvalidInput);
+	let err = io::Error::from(storekey::serialize_into_slice(&mut [0; 2], &1u32).unwrap_err());
+	assert_eq!(err.kind(), ErrorKind::WriteZero);
+}
+
+#[test]
+fn borrowed_cows() {
+	use std::borrow::Cow;
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct Cows<'a> {
+		#[serde(borrow)]
+		text: Cow<'a, str>,
+		#[serde(borrow, with = "serde_bytes")]
+		bytes: Cow<'a, [u8]>,
+		numbers: Cow<'a, [u32]>,
+		owned: Cow<'a, str>,
+	}
+
+	let value = Cows {
+		text: Cow::Borrowed("text"),
+		bytes: Cow::Borrowed(&[0, 1, 2]),
+		numbers: Cow::Borrowed(&[1, 2]),
+		owned: Cow::Borrowed("owned"),
+	};
+	let bytes = serialize(&value).unwrap();
+	let decoded: Cows = deserialize(&bytes).unwrap();
+	assert_eq!(decoded, value);
+	assert!(matches!(decoded.text, Cow::Borrowed(_)));
+	assert!(matches!(decoded.bytes, Cow::Borrowed(_)));
+	assert!(matches!(decoded.numbers, Cow::Owned(_)));
+	assert!(matches!(decoded.owned, Cow::Owned(_)));
+
+	let decoded: Cows = deserialize_from(&bytes[..]).unwrap();
+	assert!(matches!(decoded.text, Cow::Owned(_)));
+}
+
+#[test]
+fn binary_heaps() {
+	use std::collections::BinaryHeap;
+
+	#[derive(Debug, Serialize, Deserialize)]
+	struct Queue(#[serde(with = "storekey::binary_heap")] BinaryHeap<u64>);
+
+	let a = Queue(BinaryHeap::from(vec![3, 1, 4, 1, 5]));
+	let mut b = Queue(BinaryHeap::new());
+	for v in [5, 4, 3, 1, 1] {
+		b.0.push(v);
+	}
+
+	let bytes = serialize(&a).unwrap();
+	assert_eq!(bytes, serialize(&b).unwrap());
+	assert_eq!(bytes, serialize(&vec![1u64, 1, 3, 4, 5]).unwrap());
+
+	let decoded: Queue = deserialize(&bytes).unwrap();
+	assert_eq!(decoded.0.into_sorted_vec(), a.0.into_sorted_vec());
+}
+
+#[test]
+fn owned_from_buffers() {
+	use std::ops::Deref;
+
+	// Stands in for a memory map, which dereferences to its contents.
+	struct Mapped(Vec<u8>);
+
+	impl Deref for Mapped {
+		type Target = [u8];
+
+		fn deref(&self) -> &[u8] {
+			&self.0
+		}
+	}
+
+	let map = Mapped(serialize(&("outlives", 1u8)).unwrap());
+	let borrowed: (&str, u8) = deserialize(&map).unwrap();
+	assert_eq!(borrowed, ("outlives", 1));
+
+	let owned: (String, u8) = deserialize(&map).unwrap();
+	drop(map);
+	assert_eq!(owned, ("outlives".to_owned(), 1));
+}
+
+#[test]
+fn counted_sections() {
+	use storekey::{serialize_into, Deserializer};
+
+	let values = ["a".to_owned(), "bc".to_owned(), "".to_owned()];
+	let mut bytes = serialize(&(values.len() as u32)).unwrap();
+	for value in &values {
+		serialize_into(&mut bytes, value).unwrap();
+	}
+	serialize_into(&mut bytes, &9u8).unwrap();
+
+	let mut deserializer = Deserializer::from_slice(&bytes);
+	let n = u32::deserialize(&mut deserializer).unwrap();
+	let decoded: Vec<String> = deserializer.deserialize_n(n as usize).unwrap();
+	assert_eq!(decoded, values);
+	assert_eq!(u8::deserialize(&mut deserializer).unwrap(), 9);
+	assert_eq!(serialize(&("a", "bc", "")).unwrap(), bytes[4..bytes.len() - 1]);
+
+	let mut deserializer = Deserializer::from_slice(&bytes[4..]);
+	assert!(deserializer.deserialize_n::<String>(5).is_err());
+	let mut deserializer = Deserializer::from_slice(&[]);
+	assert_eq!(deserializer.deserialize_n::<u64>(0).unwrap(), []);
+}
+
+#[test]
+fn trailing_fields() {
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct V1 {
+		id: u32,
+		name: String,
+	}
+
+	#[derive(Debug, PartialEq, Serialize, Deserialize)]
+	struct V2 {
+		id: u32,
+		name: String,
+		#[serde(with = "storekey::trailing")]
+		parent: Option<u32>,
+		#[serde(with = "storekey::trailing")]
+		tags: Option<Vec<String>>,
+	}
+
+	let old = serialize(&V1 {
+		id: 1,
+		name: "a".to_owned(),
+	})
+	.unwrap();
+	let upgraded: V2 = deserialize(&old).unwrap();
+	assert_eq!(
+		upgraded,
+		V2 {
+			id: 1,
+			name: "a".to_owned(),
+			parent: None,
+			tags: None,
+		}
+	);
+
+	let new = V2 {
+		id: 1,
+		name: "a".to_owned(),
+		parent: Some(2),
+		tags: None,
+	};
+	let bytes = serialize(&new).unwrap();
+	assert_eq!(bytes, serialize(&(1u32, "a", Some(2u32), None::<()>)).unwrap());
+	assert_eq!(deserialize::<V2>(&bytes).unwrap(), new);
+	assert_eq!(deserialize_from::<_, V2>(&old[..]).unwrap(), upgraded);
+
+	// Old values sort before new values with the same leading fields.
+	assert!(old < bytes);
+
+	// Missing fields which are not trailing options are still an error.
+	assert!(deserialize::<V2>(&old[..old.len() - 1]).is_err());
+}